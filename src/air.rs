@@ -1,12 +1,13 @@
 use std::f32::consts::PI;
 
 use crate::{
+    air_sim::AirState,
     car::Car,
     constants::*,
-    pytypes::{BasicShotInfo, ShotType},
+    pytypes::{BasicShotInfo, ShotRejection, ShotType},
+    shot::AirBasedShot,
     BoostAmount, Mutators,
 };
-use dubins_paths::{NoPathError, Result as DubinsResult};
 use glam::Vec3A;
 
 #[inline]
@@ -14,10 +15,111 @@ fn angle_3d(a: Vec3A, b: Vec3A) -> f32 {
     a.dot(b).clamp(-1., 1.).acos()
 }
 
+/// Estimation of how long it will take the car to turn `phi` radians
+///
+/// `rate` is how fast the car is already turning towards the target, in radians per second
+#[inline]
+pub fn turn_time(phi: f32, rate: f32) -> f32 {
+    ((rate * rate + 4. * phi).sqrt() - rate) / 2.
+}
+
+/// Estimation of when the car will be pointed close enough to the target to start boosting
+#[inline]
+fn boost_start_time(phi: f32, rate: f32) -> f32 {
+    turn_time(phi, rate) * (1. - AERIAL_START_BOOST_ANGLE / phi.max(f32::EPSILON)).clamp(0., 1.)
+}
+
+/// How fast the car's nose is currently turning towards `direction`, in radians per second
+#[inline]
+fn turn_rate(forward: Vec3A, angular_velocity: Vec3A, direction: Vec3A) -> f32 {
+    forward.cross(direction).try_normalize().map_or(0., |axis| angular_velocity.dot(axis))
+}
+
+#[inline]
+fn steer_pd(angle: f32, rate: f32) -> f32 {
+    ((35. * (angle + rate)).powi(3) / 10.).clamp(-1., 1.)
+}
+
+/// Get the pitch, yaw and roll that will point the car's nose along `direction` while keeping it upright
+pub fn orientation_controls(state: &AirState, direction: Vec3A) -> (f32, f32, f32) {
+    let local_target = state.localize(direction);
+    let local_up = state.localize(Vec3A::Z);
+    let local_angular_velocity = state.localize(state.angular_velocity);
+
+    let pitch = steer_pd(local_target.z.atan2(local_target.x), local_angular_velocity.y / 4.);
+    let yaw = steer_pd(local_target.y.atan2(local_target.x), -local_angular_velocity.z / 4.);
+    let roll = steer_pd(local_up.y.atan2(local_up.z), local_angular_velocity.x / 2.);
+
+    (pitch, yaw, roll)
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AerialControls {
+    pub throttle: f32,
+    pub boost: bool,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
+}
+
+/// Get the controls that will get the car to the target in the air at the given time
+///
+/// `boost` is how much boost the car can use, from `Mutators::usable_boost`
+pub fn aerial_controls(state: &AirState, gravity: Vec3A, target: Vec3A, time_remaining: f32, mutators: Mutators, boost: f32) -> AerialControls {
+    let time_remaining = time_remaining.max(SIMULATION_DT);
+
+    // where we'll end up if we don't do anything, and the direction we have to accelerate in to fix that
+    let xf = state.location + state.velocity * time_remaining + gravity * 0.5 * time_remaining.powi(2);
+    let delta_x = target - xf;
+    let direction = delta_x.try_normalize().unwrap_or_else(|| (target - state.location).normalize_or_zero());
+
+    let (pitch, yaw, roll) = orientation_controls(state, direction);
+
+    let phi = angle_3d(state.forward, direction);
+    let is_aligned = phi < AERIAL_START_BOOST_ANGLE;
+
+    // when we start boosting, since the time spent turning is time we can't spend accelerating
+    let tau1 = boost_start_time(phi, turn_rate(state.forward, state.angular_velocity, direction));
+    let boost_time = (time_remaining - tau1).max(SIMULATION_DT);
+    let required_acc = 2. * delta_x.length() / boost_time.powi(2);
+
+    // when we stop boosting, the same as in `BasicAerialInfo::validate`
+    let ratio = required_acc / (mutators.boost_accel + AERIAL_THROTTLE_ACCEL);
+    let tau2 = time_remaining - boost_time * (1. - ratio.clamp(0., 1.)).sqrt();
+
+    let boost = is_aligned && boost >= BOOST_CONSUMPTION_DT && tau2 - tau1 >= SIMULATION_DT;
+
+    AerialControls {
+        // throttling the whole way would overshoot if less acceleration than that is needed
+        throttle: if is_aligned && (boost || required_acc >= AERIAL_THROTTLE_ACCEL) { 1. } else { 0. },
+        boost,
+        pitch,
+        yaw,
+        roll,
+    }
+}
+
+/// If the car should be holding jump this tick to perform the given jump type
+///
+/// `double_jump_delay` is how long after the first jump the second jump of a double jump happens
+pub fn should_jump(car: &Car, jump_type: AerialJumpType, double_jump_delay: f32) -> bool {
+    match jump_type {
+        AerialJumpType::None => false,
+        AerialJumpType::Secondary => !car.doublejumped,
+        AerialJumpType::Normal => !car.airborne || car.air_time < JUMP_MAX_DURATION,
+        AerialJumpType::Double => {
+            // release the jump button for at least a tick between the two jumps
+            !car.airborne || car.air_time < JUMP_MAX_DURATION || (car.air_time >= double_jump_delay - SIMULATION_DT && !car.doublejumped)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AerialTargetInfo {
     pub shot_vector: Vec3A,
     pub jump_type: AerialJumpType,
+    /// how long after the first jump to do the second jump, only used by double jumps
+    pub double_jump_delay: f32,
     pub final_target: Vec3A,
 }
 
@@ -28,30 +130,42 @@ impl AerialTargetInfo {
     }
 }
 
-/// Estimation of if a pre-established aerial shot is still possible
-pub fn partial_validate(target: Vec3A, xf: Vec3A, vf: Vec3A, boost_amount: BoostAmount, boost_accel: f32, car_boost: f32, time_remaining: f32) -> bool {
-    let delta_x = target - xf;
+/// Estimation of if a pre-established aerial shot is still possible from the car's current state
+pub fn is_still_valid(car: &Car, shot: &AirBasedShot, gravity: Vec3A, mutators: Mutators, time_remaining: f32) -> bool {
+    let vf = car.velocity + gravity * time_remaining;
+    let xf = car.location + car.velocity * time_remaining + gravity * 0.5 * time_remaining.powi(2);
+
+    let delta_x = shot.final_target - xf;
     let Some(f) = delta_x.try_normalize() else {
         return true;
     };
 
-    let required_acc = delta_x.length() / time_remaining.powi(2);
-    let ratio = required_acc / boost_accel;
+    // time until we're pointed close enough to the target to start boosting
+    let phi = angle_3d(f, car.forward);
+    let tau1 = boost_start_time(phi, turn_rate(car.forward, car.angular_velocity, f));
+    if tau1 >= time_remaining {
+        return false;
+    }
+
+    let boost_time = time_remaining - tau1;
+    let required_acc = delta_x.length() / boost_time.powi(2);
+    let ratio = required_acc / mutators.boost_accel;
     if ratio.abs() > 1. {
         return false;
     }
 
-    let tau2 = time_remaining - time_remaining * (1. - ratio).sqrt();
-    if boost_amount != BoostAmount::Unlimited && (tau2.floor() * BOOST_CONSUMPTION).ceil() >= car_boost {
+    let tau2 = boost_time - boost_time * (1. - ratio).sqrt();
+    if mutators.boost_amount != BoostAmount::Unlimited && (tau2.floor() * BOOST_CONSUMPTION).ceil() >= f32::from(car.boost) {
         return false;
     }
 
-    (vf + f * (boost_accel * tau2)).length() <= MAX_SPEED
+    (vf + f * (mutators.boost_accel * tau2)).length() <= MAX_SPEED
 }
 
 #[derive(Debug)]
 struct BasicAerialInfo {
     car_forward: Vec3A,
+    car_angular_velocity: Vec3A,
     car_boost: f32,
     boost_amount: BoostAmount,
     boost_accel: f32,
@@ -66,13 +180,13 @@ impl BasicAerialInfo {
         let f = delta_x.try_normalize()?;
         let phi = angle_3d(f, self.car_forward);
 
-        let turn_time = 3. * (phi / 9.).sqrt();
-        if turn_time > self.time_remaining {
+        let rate = turn_rate(self.car_forward, self.car_angular_velocity, f);
+        if turn_time(phi, rate) > self.time_remaining {
             return None;
         }
 
         // when we start boosting
-        let tau1 = turn_time * (1. - AERIAL_START_BOOST_ANGLE / phi.max(f32::EPSILON)).clamp(0., 1.);
+        let tau1 = boost_start_time(phi, rate);
 
         let required_acc = 2. * delta_x.length() / (self.time_remaining - tau1).powi(2);
         let ratio = required_acc / self.boost_accel;
@@ -98,6 +212,7 @@ impl BasicAerialInfo {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AerialJumpType {
     Secondary = -1,
     None = 0,
@@ -113,29 +228,25 @@ pub fn aerial_shot_is_viable(
     shot_vector: Vec3A,
     time_remaining: f32,
     check_target_angle: Option<Vec3A>,
-) -> DubinsResult<AerialTargetInfo> {
+) -> Result<AerialTargetInfo, ShotRejection> {
     let is_on_ground = !car.airborne || time_remaining > car.landing_time;
 
     if is_on_ground && car.up.z >= 0. && time_remaining <= JUMP_MAX_DURATION {
-        return Err(NoPathError);
+        return Err(ShotRejection::NotEnoughJumpTime);
     }
 
     let land_time = if car.landing_time > f32::EPSILON { car.landing_time } else { car.last_landing_time };
     if is_on_ground && land_time + 0.6 > time_remaining {
-        return Err(NoPathError);
+        return Err(ShotRejection::TooSoonAfterLanding);
     }
 
     let quick_speed_required = car.location.distance(target) / time_remaining;
-    if quick_speed_required > MAX_SPEED {
-        return Err(NoPathError);
-    }
-
     let max_car_speed = (car.velocity.length() + mutators.boost_accel * time_remaining).min(MAX_SPEED);
     if quick_speed_required > max_car_speed {
-        return Err(NoPathError);
+        return Err(ShotRejection::AerialTooFar);
     }
 
-    let mut found: Vec<(AerialJumpType, f32)> = Vec::with_capacity(3);
+    let mut found: Vec<(AerialJumpType, f32, f32)> = Vec::with_capacity(DOUBLE_JUMP_DELAYS.len() + 3);
 
     let boost_accel = mutators.boost_accel + AERIAL_THROTTLE_ACCEL;
 
@@ -151,6 +262,7 @@ pub fn aerial_shot_is_viable(
 
         let basic_aerial_info = BasicAerialInfo {
             car_forward: car.landing_forward,
+            car_angular_velocity: Vec3A::ZERO,
             car_boost: f32::from(car.boost),
             boost_amount: mutators.boost_amount,
             boost_accel,
@@ -159,28 +271,42 @@ pub fn aerial_shot_is_viable(
             // car,
         };
 
-        if time_remaining > DOUBLE_JUMP_DURATION {
-            const TOTAL_JUMP_ACC_2: f32 = JUMP_SPEED + TOTAL_JUMP_ACC;
-            const PARITAL_JUMP_LOC: f32 = 2. * JUMP_SPEED + JUMP_ACC * JUMP_MAX_DURATION;
-            const JUMP_LOC_P2: f32 = -(JUMP_SPEED * JUMP_MAX_DURATION + 0.5 * JUMP_MAX_DURATION * JUMP_MAX_DURATION * JUMP_ACC);
+        // jump off of whatever surface we land on, starting from when we land
+        let up = car.landing_up;
+        let surface_gravity_offset = car.surface_gravity(gravity) - gravity;
+        let vf_ground = car.landing_velocity + gravity * ground_time_remaining + surface_gravity_offset * JUMP_MAX_DURATION;
+        let xf_ground = car.landing_location
+            + car.landing_velocity * ground_time_remaining
+            + gravity * 0.5 * ground_time_remaining.powi(2)
+            + surface_gravity_offset * (JUMP_MAX_DURATION * (ground_time_remaining - JUMP_MAX_DURATION / 2.));
+
+        const TOTAL_JUMP_ACC_2: f32 = JUMP_SPEED + TOTAL_JUMP_ACC;
+        const PARITAL_JUMP_LOC_2: f32 = 2. * JUMP_SPEED + JUMP_ACC * JUMP_MAX_DURATION;
+        const JUMP_LOC_P2_2: f32 = -0.5 * JUMP_MAX_DURATION * JUMP_MAX_DURATION * JUMP_ACC;
+
+        for double_jump_delay in DOUBLE_JUMP_DELAYS {
+            if ground_time_remaining <= double_jump_delay {
+                break;
+            }
 
-            let vf = vf_base + car.up * TOTAL_JUMP_ACC_2;
-            let xf = car.landing_location + xf_base + car.up * (time_remaining * PARITAL_JUMP_LOC + JUMP_LOC_P2);
+            // the later the second jump, the less height it adds
+            let vf = vf_ground + up * TOTAL_JUMP_ACC_2;
+            let xf = xf_ground + up * (ground_time_remaining * PARITAL_JUMP_LOC_2 + JUMP_LOC_P2_2 - JUMP_SPEED * double_jump_delay);
 
-            if let Some(result) = basic_aerial_info.validate(xf, vf, AerialJumpType::Double) {
-                found.push(result);
+            if let Some((jump_type, boost_estimate)) = basic_aerial_info.validate(xf, vf, AerialJumpType::Double) {
+                found.push((jump_type, double_jump_delay, boost_estimate));
             }
         }
 
-        if time_remaining > JUMP_MAX_DURATION {
+        if ground_time_remaining > JUMP_MAX_DURATION {
             const PARITAL_JUMP_LOC: f32 = JUMP_SPEED + JUMP_ACC * JUMP_MAX_DURATION;
             const JUMP_LOC_P2: f32 = -0.5 * JUMP_MAX_DURATION * JUMP_MAX_DURATION * JUMP_ACC;
 
-            let vf = vf_base + car.up * TOTAL_JUMP_ACC;
-            let xf = car.landing_location + xf_base + car.up * (time_remaining * PARITAL_JUMP_LOC + JUMP_LOC_P2);
+            let vf = vf_ground + up * TOTAL_JUMP_ACC;
+            let xf = xf_ground + up * (ground_time_remaining * PARITAL_JUMP_LOC + JUMP_LOC_P2);
 
-            if let Some(result) = basic_aerial_info.validate(xf, vf, AerialJumpType::Normal) {
-                found.push(result);
+            if let Some((jump_type, boost_estimate)) = basic_aerial_info.validate(xf, vf, AerialJumpType::Normal) {
+                found.push((jump_type, 0., boost_estimate));
             }
         }
     }
@@ -188,6 +314,7 @@ pub fn aerial_shot_is_viable(
     if target_angle_check(car.location) {
         let basic_aerial_info = BasicAerialInfo {
             car_forward: car.forward,
+            car_angular_velocity: car.angular_velocity,
             car_boost: f32::from(car.boost),
             boost_amount: mutators.boost_amount,
             boost_accel,
@@ -196,41 +323,97 @@ pub fn aerial_shot_is_viable(
             // car,
         };
 
-        if !car.doublejumped
+        if car.can_secondary_jump()
             && (!is_on_ground || (car.airborne && (car.velocity.z + gravity.z * car.landing_time) + mutators.boost_accel * car.landing_time + JUMP_SPEED > 0.))
         {
             let vf = vf_base + car.up * JUMP_SPEED;
             let xf = car.location + xf_base + car.up * (JUMP_SPEED * time_remaining);
 
-            if let Some(result) = basic_aerial_info.validate(xf, vf, AerialJumpType::Secondary) {
-                found.push(result);
+            if let Some((jump_type, boost_estimate)) = basic_aerial_info.validate(xf, vf, AerialJumpType::Secondary) {
+                found.push((jump_type, 0., boost_estimate));
             }
         }
 
         if !is_on_ground || car.up.z < 0. || (car.airborne && (car.velocity.z + gravity.z * car.landing_time) + mutators.boost_accel * car.landing_time > 0.) {
-            if let Some(result) = basic_aerial_info.validate(car.location + xf_base, vf_base, AerialJumpType::None) {
-                found.push(result);
+            if let Some((jump_type, boost_estimate)) = basic_aerial_info.validate(car.location + xf_base, vf_base, AerialJumpType::None) {
+                found.push((jump_type, 0., boost_estimate));
             }
         }
     }
 
-    if found.is_empty() {
-        return Err(NoPathError);
-    }
-
-    // println!("{found:?}");
     let min_boost_estimate = found
         .into_iter()
-        .min_by(|(jump_type, boost_estimate), (jump_type_2, boost_estimate_2)| {
-            boost_estimate
-                .partial_cmp(boost_estimate_2)
-                .unwrap_or_else(|| panic!("Invalid boost estimate: either {boost_estimate} ({jump_type:?}) or {boost_estimate_2} ({jump_type_2:?})"))
-        })
-        .ok_or(NoPathError)?;
+        .min_by(|(_, _, boost_estimate), (_, _, boost_estimate_2)| boost_estimate.total_cmp(boost_estimate_2))
+        .ok_or(ShotRejection::AerialUnreachable)?;
 
     Ok(AerialTargetInfo {
         shot_vector,
         jump_type: min_boost_estimate.0,
+        double_jump_delay: min_boost_estimate.1,
         final_target: target,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{aerial_controls, should_jump, AerialJumpType};
+    use crate::{air_sim::AirState, car::get_one, constants::*, BoostAmount, Mutators};
+    use glam::Vec3A;
+
+    #[test]
+    pub fn jump_timing() {
+        let mut car = get_one();
+        assert!(!should_jump(&car, AerialJumpType::None, 0.));
+        assert!(should_jump(&car, AerialJumpType::Normal, 0.));
+        assert!(should_jump(&car, AerialJumpType::Double, 0.45));
+
+        car.airborne = true;
+        car.jumped = true;
+        car.air_time = JUMP_MAX_DURATION + SIMULATION_DT;
+        assert!(!should_jump(&car, AerialJumpType::Normal, 0.));
+
+        // let go of jump between the two jumps of a double jump
+        assert!(!should_jump(&car, AerialJumpType::Double, 0.45));
+
+        car.air_time = 0.45;
+        assert!(should_jump(&car, AerialJumpType::Double, 0.45));
+        assert!(should_jump(&car, AerialJumpType::Secondary, 0.));
+
+        car.doublejumped = true;
+        assert!(!should_jump(&car, AerialJumpType::Double, 0.45));
+        assert!(!should_jump(&car, AerialJumpType::Secondary, 0.));
+    }
+
+    #[test]
+    pub fn boost_window() {
+        let mut state = AirState::from_car(&get_one());
+        let mutators = Mutators::new();
+
+        // far away, so we have to boost the whole way there
+        let target = state.location + state.forward * 2000.;
+        let controls = aerial_controls(&state, Vec3A::ZERO, target, 1., mutators, 100.);
+        assert!(controls.boost);
+        assert!((controls.throttle - 1.).abs() < f32::EPSILON);
+
+        // not with an empty tank, unless boost is unlimited
+        assert!(!aerial_controls(&state, Vec3A::ZERO, target, 1., mutators, mutators.usable_boost(0)).boost);
+
+        let unlimited = Mutators {
+            boost_amount: BoostAmount::Unlimited,
+            ..Mutators::new()
+        };
+        assert!(aerial_controls(&state, Vec3A::ZERO, target, 1., unlimited, unlimited.usable_boost(0)).boost);
+
+        let no_boost = Mutators {
+            boost_amount: BoostAmount::NoBoost,
+            ..Mutators::new()
+        };
+        assert!(!aerial_controls(&state, Vec3A::ZERO, target, 1., no_boost, no_boost.usable_boost(100)).boost);
+
+        // already going fast enough, so the boost window has closed
+        state.velocity = state.forward * 1000.;
+        let controls = aerial_controls(&state, Vec3A::ZERO, state.location + state.forward * 1005., 1., mutators, 100.);
+        assert!(!controls.boost);
+        assert!(controls.throttle.abs() < f32::EPSILON);
+    }
+}
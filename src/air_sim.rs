@@ -0,0 +1,205 @@
+use crate::{
+    air::{aerial_controls, AerialControls, AerialJumpType, AerialTargetInfo},
+    car::Car,
+    constants::*,
+    Mutators,
+};
+use glam::{Quat, Vec3A};
+
+/// The state of a car that's flying through the air
+#[derive(Clone, Copy, Debug)]
+pub struct AirState {
+    pub location: Vec3A,
+    pub velocity: Vec3A,
+    pub angular_velocity: Vec3A,
+    pub forward: Vec3A,
+    pub right: Vec3A,
+    pub up: Vec3A,
+}
+
+impl AirState {
+    #[inline]
+    pub const fn from_car(car: &Car) -> Self {
+        Self {
+            location: car.location,
+            velocity: car.velocity,
+            angular_velocity: car.angular_velocity,
+            forward: car.forward,
+            right: car.right,
+            up: car.up,
+        }
+    }
+
+    /// The state of the car at the moment it lands, which is when it can jump off the ground
+    #[inline]
+    pub const fn from_landing(car: &Car) -> Self {
+        Self {
+            location: car.landing_location,
+            velocity: car.landing_velocity,
+            angular_velocity: Vec3A::ZERO,
+            forward: car.landing_forward,
+            right: car.landing_right,
+            up: car.landing_up,
+        }
+    }
+
+    #[inline]
+    pub fn localize(&self, vec: Vec3A) -> Vec3A {
+        Vec3A::new(vec.dot(self.forward), vec.dot(self.right), vec.dot(self.up))
+    }
+
+    /// The roll, pitch, and yaw rates of the car
+    ///
+    /// Positive is rolling right, pitching up, and yawing right, which is the same as the controls
+    #[inline]
+    pub fn local_rates(&self) -> Vec3A {
+        Vec3A::new(
+            -self.angular_velocity.dot(self.forward),
+            -self.angular_velocity.dot(self.right),
+            self.angular_velocity.dot(self.up),
+        )
+    }
+
+    /// Simulate the car for one tick
+    pub fn step(&mut self, controls: &AerialControls, gravity: Vec3A, boost_accel: f32) {
+        let rates = self.local_rates();
+
+        // pitch and yaw are only damped when the stick isn't being held in that direction
+        let alpha = Vec3A::new(
+            ROLL_TORQUE * controls.roll + ROLL_DAMPING * rates.x,
+            PITCH_TORQUE * controls.pitch + PITCH_DAMPING * (1. - controls.pitch.abs()) * rates.y,
+            YAW_TORQUE * controls.yaw + YAW_DAMPING * (1. - controls.yaw.abs()) * rates.z,
+        );
+
+        self.angular_velocity += (-self.forward * alpha.x - self.right * alpha.y + self.up * alpha.z) * SIMULATION_DT;
+        self.angular_velocity = self.angular_velocity.clamp_length_max(MAX_ANGULAR_SPEED);
+
+        let rotation = Quat::from_scaled_axis((self.angular_velocity * SIMULATION_DT).into());
+        self.forward = rotation.mul_vec3a(self.forward).normalize();
+        self.up = rotation.mul_vec3a(self.up).normalize();
+        self.right = self.up.cross(self.forward).normalize();
+
+        let mut accel = gravity + self.forward * (AERIAL_THROTTLE_ACCEL * controls.throttle);
+
+        if controls.boost {
+            accel += self.forward * boost_accel;
+        }
+
+        self.velocity = (self.velocity + accel * SIMULATION_DT).clamp_length_max(MAX_SPEED);
+        self.location += self.velocity * SIMULATION_DT;
+    }
+}
+
+/// Fly the aerial tick-by-tick with the aerial controller to see if the car actually gets to the target in time
+pub fn simulate_aerial(car: &Car, mutators: Mutators, gravity: Vec3A, target_info: &AerialTargetInfo, time_remaining: f32) -> bool {
+    let (state, _) = fly_aerial(car, mutators, gravity, target_info, time_remaining);
+    state.location.distance(target_info.final_target) < AERIAL_SIM_TOLERANCE
+}
+
+/// Fly the aerial tick-by-tick with the aerial controller, returning the final state of the car and how much boost it has left
+pub fn fly_aerial(car: &Car, mutators: Mutators, gravity: Vec3A, target_info: &AerialTargetInfo, time_remaining: f32) -> (AirState, f32) {
+    let jumps_from_ground = matches!(target_info.jump_type, AerialJumpType::Normal | AerialJumpType::Double);
+
+    let (mut state, t) = if jumps_from_ground {
+        (AirState::from_landing(car), car.landing_time)
+    } else {
+        (AirState::from_car(car), 0.)
+    };
+
+    if target_info.jump_type != AerialJumpType::None {
+        state.velocity += state.up * JUMP_SPEED;
+    }
+
+    fly(car, mutators, gravity, target_info, time_remaining, (state, t, jumps_from_ground.then_some(0.)))
+}
+
+/// Fly the rest of a double jump aerial that has already left the ground, where the second jump might still be coming
+pub fn fly_rest_of_double_jump(car: &Car, mutators: Mutators, gravity: Vec3A, target_info: &AerialTargetInfo, time_remaining: f32) -> (AirState, f32) {
+    fly(car, mutators, gravity, target_info, time_remaining, (AirState::from_car(car), 0., Some(car.air_time)))
+}
+
+/// `jump_time` is how long it's been since the car jumped off of the ground, if it's jumping off of the ground
+fn fly(
+    car: &Car,
+    mutators: Mutators,
+    gravity: Vec3A,
+    target_info: &AerialTargetInfo,
+    time_remaining: f32,
+    (mut state, mut t, mut jump_time): (AirState, f32, Option<f32>),
+) -> (AirState, f32) {
+    let hold_gravity = car.surface_gravity(gravity);
+    let mut boost = mutators.usable_boost(car.boost);
+
+    while t < time_remaining {
+        if let Some(jump_time) = jump_time {
+            if jump_time < JUMP_MAX_DURATION {
+                state.velocity += state.up * (JUMP_ACC * SIMULATION_DT);
+            } else if target_info.jump_type == AerialJumpType::Double && (jump_time - target_info.double_jump_delay).abs() < SIMULATION_DT / 2. {
+                state.velocity += state.up * JUMP_SPEED;
+            }
+        }
+
+        let controls = aerial_controls(&state, gravity, target_info.final_target, time_remaining - t, mutators, boost);
+
+        // unlimited boost is infinite, so it stays that way
+        if controls.boost {
+            boost -= BOOST_CONSUMPTION_DT;
+        }
+
+        // walls and the ceiling hold onto the car while it's still pushing off of them
+        let gravity = if jump_time.map_or(false, |jump_time| jump_time < JUMP_MAX_DURATION) {
+            hold_gravity
+        } else {
+            gravity
+        };
+        state.step(&controls, gravity, mutators.boost_accel);
+
+        t += SIMULATION_DT;
+        if let Some(jump_time) = &mut jump_time {
+            *jump_time += SIMULATION_DT;
+        }
+    }
+
+    (state, boost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{simulate_aerial, AirState};
+    use crate::{
+        air::{aerial_shot_is_viable, turn_time, AerialControls},
+        car::get_one,
+        Mutators,
+    };
+    use glam::Vec3A;
+
+    #[test]
+    pub fn pitch_up() {
+        let mut state = AirState::from_car(&get_one());
+        let controls = AerialControls { pitch: 1., ..Default::default() };
+
+        for _ in 0..30 {
+            state.step(&controls, Vec3A::ZERO, 0.);
+        }
+
+        assert!(state.forward.z > 0.5);
+        assert!(state.right.z.abs() < 0.01);
+    }
+
+    #[test]
+    pub fn simulated_aerial() {
+        let car = get_one();
+        let gravity = Vec3A::new(0., 0., -650.);
+        let target = car.location + car.forward * 600. + Vec3A::new(0., 0., 700.);
+
+        let target_info = aerial_shot_is_viable(&car, Mutators::new(), gravity, target, car.forward, 1.6, None).unwrap();
+        assert!(simulate_aerial(&car, Mutators::new(), gravity, &target_info, 1.6));
+    }
+
+    #[test]
+    pub fn turn_time_with_rate() {
+        assert!((turn_time(1., 0.) - 1.).abs() < f32::EPSILON);
+        assert!(turn_time(1., 2.) < turn_time(1., 0.));
+        assert!(turn_time(1., -2.) > turn_time(1., 0.));
+    }
+}
@@ -1,15 +1,57 @@
 use crate::{
     air::{aerial_shot_is_viable, AerialTargetInfo},
-    car::Car,
-    ground::{angle_2d, get_turn_exit_tanget, shortest_path_in_validate, GroundTargetInfo},
-    pytypes::ShotType,
+    air_sim,
+    car::{powerslide_exit_speed, powerslide_turn_radius, turn_radius, Car},
+    constants::{BRAKING_TURN_SPEEDS, POWERSLIDE_MIN_SPEED},
+    ground::{angle_2d, get_powerslide_turn, get_turn_exit_tanget, shortest_path_in_validate, GroundTargetInfo},
+    pytypes::{BasicShotInfo, ShotRejection, ShotType},
+    shot::{AirBasedShot, GroundBasedShot, Shot, Target},
     utils::flatten,
     Mutators,
 };
-use dubins_paths::{mod2pi, DubinsPath, NoPathError, PathType, PosRot, Result as DubinsResult};
+use dubins_paths::{mod2pi, DubinsPath, PathType, PosRot};
 use glam::Vec3A;
 use rl_ball_sym::simulation::ball::Ball;
-use std::f32::consts::PI;
+use std::{f32::consts::PI, iter};
+
+/// How the car should get through the turns of a ground path
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TurnPlan {
+    /// turn at the radius of the car's max speed
+    Normal,
+    /// powerslide through the first turn
+    Powerslide,
+    /// stay at or below the given speed through the turns, braking for the second turn if needed, then accelerate out of them
+    ///
+    /// only used if the car is already going slow enough to make the first turn
+    Braking(f32),
+}
+
+/// A shot that the car can make at a ball slice
+pub enum SliceShot {
+    Ground(GroundTargetInfo),
+    Air(AerialTargetInfo),
+}
+
+impl SliceShot {
+    #[inline]
+    pub const fn get_basic_shot_info(&self, time: f32) -> BasicShotInfo {
+        match self {
+            Self::Ground(target_info) => target_info.get_basic_shot_info(time),
+            Self::Air(target_info) => target_info.get_basic_shot_info(time),
+        }
+    }
+
+    /// Turn it into a shot that can be followed, or an empty shot of the right type if the shot is only temporary
+    pub fn into_shot(self, ball: &Ball, temporary: bool) -> Shot {
+        match self {
+            Self::Ground(_) if temporary => GroundBasedShot::default().into(),
+            Self::Ground(target_info) => GroundBasedShot::from(ball, &target_info).into(),
+            Self::Air(_) if temporary => AirBasedShot::default().into(),
+            Self::Air(target_info) => AirBasedShot::from(ball, &target_info).into(),
+        }
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 pub struct Analyzer<'a> {
@@ -58,7 +100,7 @@ impl<'a> Analyzer<'a> {
 
     /// get the type of shot that will be required to hit the ball
     /// also check if that type of shot has been enabled
-    pub fn get_shot_type(&self, target: Vec3A, time_remaining: f32) -> DubinsResult<ShotType> {
+    pub fn get_shot_type(&self, target: Vec3A, time_remaining: f32) -> Result<ShotType, ShotRejection> {
         if self.car.landing_time > time_remaining {
             if self.may_aerial_shot && self.car.last_landing_time + 0.6 < time_remaining {
                 return Ok(ShotType::Aerial);
@@ -75,11 +117,15 @@ impl<'a> Analyzer<'a> {
             return Ok(ShotType::DoubleJump);
         }
 
-        if self.may_aerial_shot && self.car.last_landing_time + 0.6 < time_remaining {
-            return Ok(ShotType::Aerial);
+        if !self.may_aerial_shot {
+            return Err(ShotRejection::ShotTypeDisabled);
         }
 
-        Err(NoPathError)
+        if self.car.last_landing_time + 0.6 < time_remaining {
+            Ok(ShotType::Aerial)
+        } else {
+            Err(ShotRejection::TooSoonAfterLanding)
+        }
     }
 
     fn get_jump_info(
@@ -90,7 +136,7 @@ impl<'a> Analyzer<'a> {
         max_speed: f32,
         time_remaining: f32,
         shot_type: ShotType,
-    ) -> DubinsResult<(Option<f32>, f32)> {
+    ) -> Result<(Option<f32>, f32), ShotRejection> {
         Ok(match shot_type {
             ShotType::Ground => {
                 let distance = 320.;
@@ -114,7 +160,7 @@ impl<'a> Analyzer<'a> {
             ShotType::DoubleJump => {
                 // if we need to do a double jump but we don't even have time for a normal jump
                 if time_remaining < self.car.max_jump_time {
-                    return Err(NoPathError);
+                    return Err(ShotRejection::NotEnoughJumpTime);
                 }
 
                 let time = self.car.double_jump_time_to_height(self.gravity.z, target.z - self.car.hitbox.height / 2.);
@@ -132,7 +178,7 @@ impl<'a> Analyzer<'a> {
         !is_backwards
     }
 
-    pub fn no_target(&self, ball: &Ball, time_remaining: f32, slice_num: usize, shot_type: ShotType) -> DubinsResult<GroundTargetInfo> {
+    pub fn no_target(&self, ball: &Ball, time_remaining: f32, slice_num: usize, shot_type: ShotType) -> Result<GroundTargetInfo, ShotRejection> {
         let car_front_length = (self.car.hitbox_offset.x + self.car.hitbox.length) / 2.;
 
         let max_speed = self.get_max_speed(slice_num);
@@ -143,7 +189,7 @@ impl<'a> Analyzer<'a> {
 
         // check if a simplified path is longer than the longest distance we can possibly travel
         if car_location.distance(flatten(ball.location)) > max_distance {
-            return Err(NoPathError);
+            return Err(ShotRejection::PathTooLong);
         }
 
         let car_to_ball = (ball.location - self.car.location).normalize_or_zero();
@@ -157,7 +203,7 @@ impl<'a> Analyzer<'a> {
         if let Some(jump_time) = jump_time {
             // if we have enough time for just the jump
             if jump_time > time_remaining {
-                return Err(NoPathError);
+                return Err(ShotRejection::NotEnoughJumpTime);
             }
         }
 
@@ -172,7 +218,7 @@ impl<'a> Analyzer<'a> {
 
         // check if the exit point is in the field
         if !self.car.field.is_point_in(turn_target) {
-            return Err(NoPathError);
+            return Err(ShotRejection::OutOfField);
         }
 
         // compute the distance of each path, validating that it is within our current maximum travel distance (returning an error if neither are)
@@ -181,7 +227,7 @@ impl<'a> Analyzer<'a> {
         let offset_distance = end_distance - car_front_length - ball.radius();
 
         if turn_final_distance < offset_distance || turn_final_distance + turn_target.distance(car_location) > max_distance {
-            return Err(NoPathError);
+            return Err(ShotRejection::PathTooLong);
         }
 
         let shot_vector = (flatten(ball.location) - turn_target).normalize_or_zero();
@@ -205,7 +251,7 @@ impl<'a> Analyzer<'a> {
         let turn_arc_distance = turn_angle * rho;
 
         if turn_final_distance + turn_arc_distance > max_distance {
-            return Err(NoPathError);
+            return Err(ShotRejection::PathTooLong);
         }
 
         let enter_yaw = if travel_forwards { self.car.landing_yaw } else { mod2pi(self.car.landing_yaw + PI) };
@@ -231,7 +277,70 @@ impl<'a> Analyzer<'a> {
         ))
     }
 
-    pub fn target(&self, ball: &Ball, shot_vector: Vec3A, time_remaining: f32, slice_num: usize, shot_type: ShotType) -> DubinsResult<GroundTargetInfo> {
+    /// Find a ground path that the car can make in time
+    ///
+    /// The regular turn radius is tried first, and only if the car is too slow to make it are a powerslide and then braking into progressively tighter turns tried
+    ///
+    /// If none of them work, the reason that the regular turn radius didn't work is returned
+    pub fn target(
+        &self,
+        ball: &Ball,
+        shot_vector: Vec3A,
+        time_remaining: f32,
+        slice_num: usize,
+        shot_type: ShotType,
+        mutators: Mutators,
+    ) -> Result<GroundTargetInfo, ShotRejection> {
+        let reach = |turn_plan| {
+            self.target_with(ball, shot_vector, time_remaining, slice_num, shot_type, turn_plan)
+                .and_then(|target_info| target_info.can_reach(self.car, time_remaining, mutators).map(|_| target_info))
+        };
+
+        let rejection = match reach(TurnPlan::Normal) {
+            Ok(target_info) => return Ok(target_info),
+            Err(rejection) => rejection,
+        };
+
+        // a tighter turn only helps if the path fits but the car can't drive it fast enough
+        if rejection != ShotRejection::TooSlow {
+            return Err(rejection);
+        }
+
+        iter::once(TurnPlan::Powerslide)
+            .chain(BRAKING_TURN_SPEEDS.into_iter().map(TurnPlan::Braking))
+            .filter(|turn_plan| self.turn_plan_applies(*turn_plan, time_remaining, slice_num, shot_vector))
+            .find_map(|turn_plan| reach(turn_plan).ok())
+            .ok_or(rejection)
+    }
+
+    /// If the turn plan makes sense for how the car will be moving when it lands
+    ///
+    /// Plans that don't are skipped, instead of being a reason that the slice was rejected
+    fn turn_plan_applies(&self, turn_plan: TurnPlan, time_remaining: f32, slice_num: usize, shot_vector: Vec3A) -> bool {
+        let speed = self.car.landing_velocity.dot(self.car.landing_forward);
+
+        match turn_plan {
+            TurnPlan::Normal => true,
+            // powersliding or braking into a turn while reversing isn't something we want to do
+            _ if !self.should_travel_forwards(time_remaining - self.car.landing_time, shot_vector) => false,
+            TurnPlan::Powerslide => speed >= POWERSLIDE_MIN_SPEED,
+            // there's no point in braking if we can't go faster than the turn speed anyways,
+            // and the first turn starts right away, so the car can't brake down to the turn speed before it
+            TurnPlan::Braking(turn_speed) => turn_speed < self.get_max_speed(slice_num) && speed <= turn_speed,
+        }
+    }
+
+    fn target_with(
+        &self,
+        ball: &Ball,
+        shot_vector: Vec3A,
+        time_remaining: f32,
+        slice_num: usize,
+        shot_type: ShotType,
+        turn_plan: TurnPlan,
+    ) -> Result<GroundTargetInfo, ShotRejection> {
+        debug_assert!(self.turn_plan_applies(turn_plan, time_remaining, slice_num, shot_vector));
+
         let offset_target = ball.location - (shot_vector * ball.radius());
         let car_front_length = (self.car.hitbox_offset.x + self.car.hitbox.length) / 2.;
 
@@ -243,22 +352,26 @@ impl<'a> Analyzer<'a> {
 
         // check if a simplified path is longer than the longest distance we can possibly travel
         if flatten(car_location).distance(flatten(offset_target)) > max_distance {
-            return Err(NoPathError);
+            return Err(ShotRejection::PathTooLong);
         }
         let (jump_time, end_distance) = self.get_jump_info(ball.location, offset_target, shot_vector, max_speed, time_remaining, shot_type)?;
 
         if let Some(jump_time) = jump_time {
             // if we have enough time for just the jump
             if jump_time > time_remaining {
-                return Err(NoPathError);
+                return Err(ShotRejection::NotEnoughJumpTime);
             }
         }
 
         let exit_turn_target = flatten(offset_target) - (flatten(shot_vector).normalize_or_zero() * end_distance);
 
         // check if the exit point is in the field, and make sure a simplified version of the path isn't longer than the longest distance we can travel
-        if !self.car.field.is_point_in(flatten(exit_turn_target)) || flatten(car_location).distance(exit_turn_target) + end_distance > max_distance {
-            return Err(NoPathError);
+        if !self.car.field.is_point_in(flatten(exit_turn_target)) {
+            return Err(ShotRejection::OutOfField);
+        }
+
+        if flatten(car_location).distance(exit_turn_target) + end_distance > max_distance {
+            return Err(ShotRejection::PathTooLong);
         }
 
         // calculate and return the dubin's path
@@ -274,13 +387,55 @@ impl<'a> Analyzer<'a> {
 
         let q0 = PosRot::new(flatten(car_location), starting_yaw);
         let q1 = PosRot::new(flatten(exit_turn_target), target_angle);
+        let rho = self.get_max_turn_radius(slice_num);
 
-        let path = shortest_path_in_validate(q0, q1, self.get_max_turn_radius(slice_num), &self.car.field, max_distance)?;
+        let (path, powerslide_path) = if turn_plan == TurnPlan::Powerslide {
+            let speed = self.car.landing_velocity.dot(self.car.landing_forward);
 
-        let offset_distance = end_distance - car_front_length;
-        let distances = [path.segment_length(0), path.segment_length(1), path.segment_length(2), offset_distance];
+            // dubin's paths treat counter-clockwise as a left turn, which is the car's right in RL
+            let counter_clockwise = self.car.localize_2d_location(exit_turn_target).y > 0.;
+            let powerslide_path = get_powerslide_turn(q0, exit_turn_target, powerslide_turn_radius(speed), counter_clockwise).map_err(|_| ShotRejection::PathTooLong)?;
+            let powerslide_distance = powerslide_path.length();
+
+            // the car would come to a stop before finishing the turn
+            if powerslide_exit_speed(speed, powerslide_distance) < f32::EPSILON {
+                return Err(ShotRejection::TooSlow);
+            }
+
+            if !self.car.field.is_path_in(&powerslide_path) {
+                return Err(ShotRejection::OutOfField);
+            }
+
+            let path =
+                shortest_path_in_validate(powerslide_path.endpoint(), q1, rho, &self.car.field, max_distance - powerslide_distance).map_err(|_| ShotRejection::PathTooLong)?;
 
-        Ok(GroundTargetInfo::from(distances, shot_type, path, jump_time, is_forwards, shot_vector, None))
+            (path, Some(powerslide_path))
+        } else if let TurnPlan::Braking(turn_speed) = turn_plan {
+            (
+                shortest_path_in_validate(q0, q1, turn_radius(turn_speed).min(rho), &self.car.field, max_distance).map_err(|_| ShotRejection::PathTooLong)?,
+                None,
+            )
+        } else {
+            (
+                shortest_path_in_validate(q0, q1, rho, &self.car.field, max_distance).map_err(|_| ShotRejection::PathTooLong)?,
+                None,
+            )
+        };
+
+        let powerslide_distance = powerslide_path.map_or(0., |path| path.length());
+        let offset_distance = end_distance - car_front_length;
+        let distances = [
+            powerslide_distance + path.segment_length(0),
+            path.segment_length(1),
+            path.segment_length(2),
+            offset_distance,
+        ];
+
+        Ok(GroundTargetInfo {
+            powerslide: powerslide_path,
+            max_turn_speed: if let TurnPlan::Braking(turn_speed) = turn_plan { Some(turn_speed) } else { None },
+            ..GroundTargetInfo::from(distances, shot_type, path, jump_time, is_forwards, shot_vector, None)
+        })
     }
 
     #[inline]
@@ -291,7 +446,122 @@ impl<'a> Analyzer<'a> {
         shot_vector: Vec3A,
         time_remaining: f32,
         check_target_angle: Option<Vec3A>,
-    ) -> DubinsResult<AerialTargetInfo> {
+    ) -> Result<AerialTargetInfo, ShotRejection> {
         aerial_shot_is_viable(self.car, mutators, self.gravity, target, shot_vector, time_remaining, check_target_angle)
     }
+
+    /// Find the shot that the car can make at the ball slice, or why it can't make one
+    pub fn check_slice(&self, target: &Target, ball: &Ball, slice_num: usize, time_remaining: f32, mutators: Mutators) -> Result<SliceShot, ShotRejection> {
+        let shot_type = self.get_shot_type(ball.location, time_remaining)?;
+
+        let target_info = if let Some(target_location) = &target.location {
+            let post_info = target_location.post_correction(ball.location, ball.collision_radius());
+
+            if !post_info.fits {
+                return Err(ShotRejection::PostCorrection);
+            }
+
+            let shot_vector = post_info.get_shot_vector_target(self.car.landing_location, ball.location);
+
+            if shot_type == ShotType::Aerial {
+                let target_location = if post_info.window.is_some() {
+                    // the ball has to be hit along the whole shot vector to go through the window
+                    let ball_edge = ball.location - shot_vector * ball.radius();
+                    ball_edge - shot_vector * (self.car.hitbox_offset.x + self.car.hitbox.length) / 2.
+                } else {
+                    let ball_edge = ball.location - flatten(shot_vector) * ball.radius();
+                    ball_edge - Vec3A::new(0., 0., shot_vector.z) * (self.car.hitbox_offset.x + self.car.hitbox.length) / 2.
+                };
+
+                SliceShot::Air(self.aerial_shot(mutators, target_location, shot_vector, time_remaining, Some(ball.location))?)
+            } else {
+                SliceShot::Ground(self.target(ball, shot_vector, time_remaining, slice_num, shot_type, mutators)?)
+            }
+        } else if shot_type == ShotType::Aerial {
+            let ball_edge = ball.location - flatten(ball.location - self.car.location).normalize_or_zero() * ball.radius();
+            let shot_vector = (ball_edge - self.car.location).normalize_or_zero();
+            let target_location = ball_edge - shot_vector * (self.car.hitbox_offset.x + self.car.hitbox.length) / 2.;
+
+            SliceShot::Air(self.aerial_shot(mutators, target_location, shot_vector, time_remaining, None)?)
+        } else {
+            let target_info = self.no_target(ball, time_remaining, slice_num, shot_type)?;
+            target_info.can_reach(self.car, time_remaining, mutators)?;
+
+            SliceShot::Ground(target_info)
+        };
+
+        if let SliceShot::Air(target_info) = &target_info {
+            if target.options.simulate_aerials && !air_sim::simulate_aerial(self.car, mutators, self.gravity, target_info, time_remaining) {
+                return Err(ShotRejection::AerialSimulation);
+            }
+        }
+
+        Ok(target_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Analyzer, TurnPlan};
+    use crate::{
+        car::get_one,
+        pytypes::{ShotRejection, ShotType},
+        Mutators,
+    };
+    use glam::Vec3A;
+    use rl_ball_sym::simulation::ball::Ball;
+
+    #[test]
+    pub fn brake_into_tighter_turn() {
+        let car = get_one();
+        let analyzer = Analyzer::new((None, None), Vec3A::new(0., 0., -650.), true, true, true, true, &car);
+
+        let time_remaining = 1.5;
+        let slice_num = 180;
+        let mut ball = Ball::initialize_soccar();
+        ball.update(
+            time_remaining,
+            car.location + car.forward * 1600. + car.right * 800. + Vec3A::new(0., 0., 73.),
+            Vec3A::ZERO,
+            Vec3A::ZERO,
+        );
+        let shot_vector = car.forward * 0.5_f32.cos() + car.right * 0.5_f32.sin();
+
+        // the car can't turn sharply enough at the speed it'll be going
+        let normal = analyzer
+            .target_with(&ball, shot_vector, time_remaining, slice_num, ShotType::Ground, TurnPlan::Normal)
+            .and_then(|target_info| target_info.can_reach(&car, time_remaining, Mutators::new()));
+        assert_eq!(normal.err(), Some(ShotRejection::TooSlow));
+
+        let target_info = analyzer.target(&ball, shot_vector, time_remaining, slice_num, ShotType::Ground, Mutators::new()).unwrap();
+        assert!(target_info.max_turn_speed.is_some());
+        assert!(target_info.path.rho < car.ctrms[slice_num]);
+        assert!(target_info.can_reach(&car, time_remaining, Mutators::new()).is_ok());
+    }
+
+    #[test]
+    pub fn skip_turn_plans_that_dont_apply() {
+        let mut car = get_one();
+        let slice_num = 180;
+        let ahead = car.forward;
+        let behind = -car.forward;
+
+        // a stopped car can't powerslide, but it's already slow enough for any braking turn
+        let analyzer = Analyzer::new((None, None), Vec3A::new(0., 0., -650.), true, true, true, true, &car);
+        assert!(analyzer.turn_plan_applies(TurnPlan::Normal, 1.5, slice_num, ahead));
+        assert!(!analyzer.turn_plan_applies(TurnPlan::Powerslide, 1.5, slice_num, ahead));
+        assert!(analyzer.turn_plan_applies(TurnPlan::Braking(500.), 1.5, slice_num, ahead));
+
+        // the car is going too fast to brake down to the turn speed before the first turn
+        car.landing_velocity = car.landing_forward * 1200.;
+        let analyzer = Analyzer::new((None, None), Vec3A::new(0., 0., -650.), true, true, true, true, &car);
+        assert!(analyzer.turn_plan_applies(TurnPlan::Powerslide, 1.5, slice_num, ahead));
+        assert!(analyzer.turn_plan_applies(TurnPlan::Braking(1500.), 1.5, slice_num, ahead));
+        assert!(!analyzer.turn_plan_applies(TurnPlan::Braking(1000.), 1.5, slice_num, ahead));
+
+        // only the regular turn radius is used while reversing
+        assert!(analyzer.turn_plan_applies(TurnPlan::Normal, 1.5, slice_num, behind));
+        assert!(!analyzer.turn_plan_applies(TurnPlan::Powerslide, 1.5, slice_num, behind));
+        assert!(!analyzer.turn_plan_applies(TurnPlan::Braking(1500.), 1.5, slice_num, behind));
+    }
 }
@@ -6,6 +6,7 @@ use crate::{
 };
 use dubins_paths::DubinsPath;
 use glam::Vec3A;
+use rl_ball_sym::simulation::{ball::Ball, game::Game};
 
 pub fn throttle_acceleration(forward_velocity: f32) -> f32 {
     let x = forward_velocity.abs();
@@ -33,11 +34,9 @@ pub fn curvature(v: f32) -> f32 {
         0.0043 - 1.95e-6 * v
     } else if (1500. ..1750.).contains(&v) {
         0.003_025 - 1.1e-6 * v
-    } else if (1750. ..2305.).contains(&v) {
-        0.0018 - 4e-7 * v
     } else {
-        println!("Invalid input velocity: {v}");
-        -1.
+        // the car can't go faster than MAX_SPEED, so anything above it is just rounding error
+        0.0018 - 4e-7 * v.min(MAX_SPEED)
     }
 }
 
@@ -46,7 +45,34 @@ pub fn turn_radius(v: f32) -> f32 {
     1. / curvature(v)
 }
 
+/// The turn radius while holding the handbrake at full steer
+#[inline]
+pub fn powerslide_turn_radius(v: f32) -> f32 {
+    turn_radius(v) / POWERSLIDE_CURVATURE_SCALE
+}
+
+/// The speed the car will have after powersliding for the given distance
+#[inline]
+pub fn powerslide_exit_speed(v: f32, distance: f32) -> f32 {
+    (v * v - 2. * POWERSLIDE_DECEL * distance).max(0.).sqrt()
+}
+
+/// Check if a sphere touches the arena's collision mesh, and get the normal of the surface that it's touching
+///
+/// rl_ball_sym doesn't expose its mesh queries, but stepping a ball for no time at all only resolves its collision with the mesh.
+/// Gravity, drag, and movement are all scaled by the time step, so the only thing that can move the probe is getting pushed out of the mesh.
+/// The probe is given a spin because balls that aren't moving or spinning skip the collision check.
+/// This isn't documented behaviour, which is why rl_ball_sym is pinned to an exact version in Cargo.toml.
+pub fn arena_contact(game: &Game, probe: &mut Ball, location: Vec3A) -> Option<Vec3A> {
+    probe.update(probe.time, location, Vec3A::ZERO, Vec3A::X);
+    probe.step(game, 0.);
+
+    let push = probe.location - location;
+    (push.length() > ARENA_HIT_TOLERANCE).then(|| push.normalize())
+}
+
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldRect {
     goal_x: f32,
     goal_y: f32,
@@ -106,6 +132,7 @@ impl FieldRect {
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Car {
     pub location: Vec3A,
     pub velocity: Vec3A,
@@ -125,6 +152,8 @@ pub struct Car {
     pub airborne: bool,
     pub jumped: bool,
     pub doublejumped: bool,
+    /// if the car has regained its jump by touching the ball with its wheels
+    pub has_flip_reset: bool,
     pub landing_time: f32,
     pub landing_location: Vec3A,
     pub landing_velocity: Vec3A,
@@ -134,6 +163,9 @@ pub struct Car {
     pub landing_up: Vec3A,
     last_landing_game_time: f32,
     pub last_landing_time: f32,
+    last_takeoff_game_time: f32,
+    /// how long the car has been in the air, or 0 if it's on the ground
+    pub air_time: f32,
     pub max_speed: Vec<f32>,
     /// turn radius at calculated max speed
     pub ctrms: Vec<f32>,
@@ -166,6 +198,7 @@ impl Car {
             airborne: false,
             jumped: false,
             doublejumped: false,
+            has_flip_reset: false,
             landing_time: 0.,
             landing_location: Vec3A::ZERO,
             landing_velocity: Vec3A::ZERO,
@@ -175,6 +208,8 @@ impl Car {
             landing_up: Vec3A::ZERO,
             last_landing_game_time: 0.,
             last_landing_time: 0.,
+            last_takeoff_game_time: 0.,
+            air_time: 0.,
             max_speed: Vec::new(),
             ctrms: Vec::new(),
             max_jump_time: 0.,
@@ -199,26 +234,51 @@ impl Car {
 
         self.boost = py_car.boost;
         self.demolished = py_car.is_demolished;
+        let airborne = !py_car.has_wheel_contact;
+
+        // the second jump coming back while we're still in the air means we got a flip reset, which lasts until it's used or we land
+        self.has_flip_reset = airborne && self.airborne && !py_car.double_jumped && (self.has_flip_reset || self.doublejumped);
+
         self.jumped = py_car.jumped;
         self.doublejumped = py_car.double_jumped;
 
-        let airborne = !py_car.has_wheel_contact;
-
         if self.airborne && !airborne {
             self.last_landing_game_time = game_time;
+        } else if !self.airborne && airborne {
+            self.last_takeoff_game_time = game_time;
         }
 
         self.last_landing_time = self.last_landing_game_time - game_time;
+        self.air_time = if airborne { game_time - self.last_takeoff_game_time } else { 0. };
         self.airborne = airborne;
 
         self.init = false;
     }
 
-    pub fn init(&mut self, gravity: f32, max_ball_slice: usize, mutators: Mutators) {
+    /// The gravity that affects the car while it's still pushing off of the surface it lands on
+    ///
+    /// Walls and the ceiling hold the car against the part of gravity that's pulling it off of them
+    #[inline]
+    pub fn surface_gravity(&self, gravity: Vec3A) -> Vec3A {
+        if self.landing_up.z > 0.9 {
+            gravity
+        } else {
+            gravity.reject_from(self.landing_up)
+        }
+    }
+
+    /// If the car still has a jump that it can use in the air
+    #[inline]
+    pub fn can_secondary_jump(&self) -> bool {
+        !self.doublejumped && (self.has_flip_reset || !self.jumped || self.air_time < DOUBLE_JUMP_TIMEOUT)
+    }
+
+    /// `game` is used to find where the car will land in the arena, and the walls and ceiling are assumed to be flat when it isn't given
+    pub fn init(&mut self, gravity: f32, game: Option<&Game>, max_ball_slice: usize, mutators: Mutators) {
         if !self.init {
             Self::calculate_orientation_matrix(&mut self.forward, &mut self.right, &mut self.up, self.pitch, self.yaw, self.roll);
             self.calculate_field();
-            self.calculate_landing_info(gravity);
+            self.calculate_landing_info(gravity, game);
             self.calculate_local_values();
             self.calculate_max_values(max_ball_slice, mutators);
             self.calculate_max_jump_height(gravity);
@@ -290,7 +350,7 @@ impl Car {
         self.max_double_jump_height = l_z;
     }
 
-    pub fn calculate_landing_info(&mut self, gravity: f32) {
+    pub fn calculate_landing_info(&mut self, gravity: f32, game: Option<&Game>) {
         self.landing_time = 0.;
         self.landing_location = self.location;
         self.landing_velocity = self.velocity;
@@ -312,7 +372,11 @@ impl Car {
         let k = self.velocity.z * self.velocity.z / -gravity;
 
         let normal_gravity = gravity < 0.;
-        let fall_distance = if normal_gravity { self.location.z - 17. } else { 2030. - self.location.z };
+        let fall_distance = if normal_gravity {
+            self.location.z - CAR_REST_HEIGHT
+        } else {
+            ARENA_CEILING - CAR_REST_HEIGHT - self.location.z
+        };
 
         let terminal_velocity = 2300. - flatten(self.velocity).length();
         let time_until_tv = terminal_velocity / -gravity;
@@ -343,7 +407,11 @@ impl Car {
                 let v_t = (self.landing_velocity + g_dt).normalize_or_zero() * 2300.;
                 let l_t = self.landing_location + self.landing_velocity * dt;
 
-                let l_z = if normal_gravity { l_t[2] - 17. } else { 2030. - l_t[2] };
+                let l_z = if normal_gravity {
+                    l_t[2] - CAR_REST_HEIGHT
+                } else {
+                    ARENA_CEILING - CAR_REST_HEIGHT - l_t[2]
+                };
 
                 if l_z <= 0. {
                     break;
@@ -354,7 +422,38 @@ impl Car {
                 self.landing_time += dt;
             }
 
-            self.landing_location.z = if normal_gravity { 17. } else { 2300. };
+            self.landing_location.z = if normal_gravity { CAR_REST_HEIGHT } else { ARENA_CEILING - CAR_REST_HEIGHT };
+        }
+
+        let mut surface_normal = if normal_gravity { Vec3A::Z } else { Vec3A::NEG_Z };
+
+        // a wall or the ceiling might get in the way before we're back on the floor
+        let surface_hit = match game {
+            Some(game) => self.find_arena_hit(game, gravity),
+            None => self.find_surface_hit(gravity),
+        };
+
+        if let Some((time, normal)) = surface_hit {
+            let gravity = Vec3A::new(0., 0., gravity);
+
+            self.landing_time = time;
+            self.landing_location = self.location + self.velocity * time + gravity * (0.5 * time * time);
+            self.landing_velocity = self.velocity + gravity * time;
+            surface_normal = normal;
+        }
+
+        // the surface absorbs the part of the velocity that goes into it
+        self.landing_velocity -= surface_normal * self.landing_velocity.dot(surface_normal);
+
+        if surface_normal.z < 0.99 {
+            self.landing_up = surface_normal;
+            self.landing_forward = self
+                .landing_velocity
+                .try_normalize()
+                .or_else(|| self.forward.reject_from(surface_normal).try_normalize())
+                .unwrap_or_else(|| surface_normal.any_orthonormal_vector());
+            self.landing_right = self.landing_up.cross(self.landing_forward);
+            return;
         }
 
         if flatten(self.landing_velocity).length() != 0. {
@@ -364,6 +463,67 @@ impl Car {
         Self::calculate_orientation_matrix(&mut self.landing_forward, &mut self.landing_right, &mut self.landing_up, 0., self.landing_yaw, 0.);
     }
 
+    /// Find the first time the car's trajectory hits the arena's collision mesh before landing on the floor
+    ///
+    /// Returns the time of the hit and the normal of the surface that was hit
+    fn find_arena_hit(&self, game: &Game, gravity: f32) -> Option<(f32, Vec3A)> {
+        let gravity = Vec3A::new(0., 0., gravity);
+
+        let mut probe = Ball::const_default();
+        probe.set_radius(CAR_REST_HEIGHT, CAR_REST_HEIGHT);
+
+        let mut time = SIMULATION_DT;
+
+        while time < self.landing_time {
+            let location = self.location + self.velocity * time + gravity * (0.5 * time * time);
+
+            if let Some(normal) = arena_contact(game, &mut probe, location) {
+                return Some((time, normal));
+            }
+
+            time += SIMULATION_DT;
+        }
+
+        None
+    }
+
+    /// Find the first time the car's trajectory hits a side wall, back wall, or the ceiling before landing on the floor
+    ///
+    /// Returns the time of the hit and the normal of the surface that was hit
+    fn find_surface_hit(&self, gravity: f32) -> Option<(f32, Vec3A)> {
+        let mut hit: Option<(f32, Vec3A)> = None;
+        let mut check = |time: f32, normal: Vec3A| {
+            if time > 0. && time < hit.map_or(self.landing_time, |(t, _)| t) {
+                hit = Some((time, normal));
+            }
+        };
+
+        if self.velocity.x != 0. {
+            let sign = self.velocity.x.signum();
+            check((sign * (ARENA_SIDE_WALL - CAR_REST_HEIGHT) - self.location.x) / self.velocity.x, Vec3A::new(-sign, 0., 0.));
+        }
+
+        if self.velocity.y != 0. {
+            let sign = self.velocity.y.signum();
+            let time = (sign * (ARENA_BACK_WALL - CAR_REST_HEIGHT) - self.location.y) / self.velocity.y;
+            let location = self.location + self.velocity * time + Vec3A::new(0., 0., 0.5 * gravity * time * time);
+
+            // the goals are in the back walls
+            if location.x.abs() > GOAL_HALF_WIDTH || location.z > GOAL_HEIGHT {
+                check(time, Vec3A::new(0., -sign, 0.));
+            }
+        }
+
+        if gravity < 0. {
+            let h = self.velocity.z / -gravity;
+            let k = self.velocity.z * self.velocity.z / (-2. * gravity);
+            let (time1, time2) = vertex_quadratic_solve_for_x(0.5 * gravity, h, k, ARENA_CEILING - CAR_REST_HEIGHT - self.location.z);
+            check(minimum_non_negative(time1, time2), Vec3A::NEG_Z);
+        }
+
+        hit
+    }
+
     pub fn calculate_orientation_matrix(forward: &mut Vec3A, right: &mut Vec3A, up: &mut Vec3A, pitch: f32, yaw: f32, roll: f32) {
         let (s_p, c_p) = pitch.sin_cos();
         let (s_y, c_y) = yaw.sin_cos();
@@ -621,17 +781,80 @@ pub fn get_one() -> Car {
     car.jumped = false;
     car.doublejumped = false;
 
-    car.init(-650., 720, Mutators::new());
+    car.init(-650., None, 720, Mutators::new());
 
     car
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::car::get_one;
+    use crate::{
+        car::{arena_contact, get_one, turn_radius},
+        constants::*,
+    };
+    use glam::Vec3A;
+    use rl_ball_sym::simulation::ball::Ball;
 
     #[test]
     pub fn init_car() {
         get_one();
     }
+
+    #[test]
+    pub fn turn_radius_above_max_speed() {
+        assert!((turn_radius(MAX_SPEED + 10.) - turn_radius(MAX_SPEED)).abs() < f32::EPSILON);
+        assert!(turn_radius(MAX_SPEED + 10.) > 0.);
+    }
+
+    #[test]
+    pub fn wall_landing() {
+        let mut car = get_one();
+        car.location = Vec3A::new(3800., 0., 1000.);
+        car.velocity = Vec3A::new(1000., 0., 0.);
+        car.airborne = true;
+        car.calculate_landing_info(-650., None);
+
+        assert!((car.landing_location.x - (ARENA_SIDE_WALL - CAR_REST_HEIGHT)).abs() < 1.);
+        assert!(car.landing_up.x < -0.99);
+        assert!(car.landing_velocity.x.abs() < f32::EPSILON);
+    }
+
+    #[test]
+    pub fn arena_wall_landing() {
+        let (game, _) = rl_ball_sym::compressed::load_soccar();
+
+        let mut car = get_one();
+        car.location = Vec3A::new(3800., 0., 1000.);
+        car.velocity = Vec3A::new(1000., 0., 0.);
+        car.airborne = true;
+        car.calculate_landing_info(-650., Some(&game));
+
+        assert!(car.landing_location.x > 4000.);
+        assert!(car.landing_up.x < -0.99);
+        assert!(car.landing_forward.dot(car.landing_up).abs() < 0.01);
+    }
+
+    #[test]
+    pub fn arena_contact_normal() {
+        let (game, _) = rl_ball_sym::compressed::load_soccar();
+
+        let mut probe = Ball::const_default();
+        probe.set_radius(CAR_REST_HEIGHT, CAR_REST_HEIGHT);
+
+        assert!(arena_contact(&game, &mut probe, Vec3A::new(0., 0., 500.)).is_none());
+        assert!(arena_contact(&game, &mut probe, Vec3A::new(0., 0., 10.)).unwrap().z > 0.99);
+        assert!(arena_contact(&game, &mut probe, Vec3A::new(ARENA_SIDE_WALL - 10., 0., 500.)).unwrap().x < -0.99);
+    }
+
+    #[test]
+    pub fn flip_reset_secondary_jump() {
+        let mut car = get_one();
+        car.airborne = true;
+        car.jumped = true;
+        car.air_time = 2.;
+        assert!(!car.can_secondary_jump());
+
+        car.has_flip_reset = true;
+        assert!(car.can_secondary_jump());
+    }
 }
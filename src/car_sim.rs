@@ -0,0 +1,261 @@
+use crate::{
+    air::AerialControls,
+    air_sim::AirState,
+    car::{powerslide_turn_radius, throttle_acceleration, turn_radius, Car},
+    constants::*,
+    pytypes::{ControllerState, GameCar, GamePhysics, GameRot, GameVec, Hitbox},
+    utils::flatten,
+    Mutators,
+};
+use glam::{Quat, Vec3A};
+
+/// How fast a car on the ground that's going `v` forwards speeds up with the given controls
+pub fn ground_acceleration(v: f32, throttle: f32, boosting: bool, handbrake: bool, mutators: Mutators) -> f32 {
+    // coasting, braking, and powersliding only slow the car down, so they don't do anything when it's already stopped
+    let moving = v.abs() > f32::EPSILON;
+
+    let mut accel = if throttle * v < 0. {
+        -BRAKE_ACC * v.signum()
+    } else if throttle.abs() < f32::EPSILON {
+        if moving {
+            -COAST_ACC * v.signum()
+        } else {
+            0.
+        }
+    } else {
+        throttle_acceleration(v) * throttle
+    };
+
+    if boosting {
+        accel += mutators.boost_accel;
+    }
+
+    if handbrake && moving {
+        accel -= POWERSLIDE_DECEL * v.signum();
+    }
+
+    accel
+}
+
+/// Steps a car forward at 120tps with the given controls
+///
+/// Driving is only simulated on the floor, and dodges aren't simulated - a second jump is always a double jump
+#[derive(Clone, Copy, Debug)]
+pub struct CarSim {
+    pub time: f32,
+    pub state: AirState,
+    /// how much boost the car can use, from `Mutators::usable_boost`
+    pub boost: f32,
+    pub on_ground: bool,
+    pub jumped: bool,
+    pub doublejumped: bool,
+    /// how long it's been since the car jumped off of the ground
+    pub jump_time: f32,
+    holding_jump: bool,
+    last_jump: bool,
+    gravity: Vec3A,
+    mutators: Mutators,
+}
+
+impl CarSim {
+    pub fn from(car: &Car, gravity: Vec3A, mutators: Mutators, time: f32) -> Self {
+        Self {
+            time,
+            state: AirState::from_car(car),
+            boost: mutators.usable_boost(car.boost),
+            on_ground: !car.airborne,
+            jumped: car.jumped,
+            doublejumped: car.doublejumped,
+            jump_time: car.air_time,
+            holding_jump: false,
+            last_jump: false,
+            gravity,
+            mutators,
+        }
+    }
+
+    /// Simulate the car for one tick
+    pub fn step(&mut self, controls: &ControllerState) {
+        let jump_pressed = controls.jump && !self.last_jump;
+        self.last_jump = controls.jump;
+        self.holding_jump &= controls.jump;
+
+        // unlimited boost is infinite, so it never runs out
+        let boosting = controls.boost && self.boost >= BOOST_CONSUMPTION_DT;
+
+        if boosting {
+            self.boost -= BOOST_CONSUMPTION_DT;
+        }
+
+        if self.on_ground && jump_pressed {
+            self.on_ground = false;
+            self.jumped = true;
+            self.holding_jump = true;
+            self.jump_time = 0.;
+            self.state.velocity += self.state.up * JUMP_SPEED;
+        } else if !self.on_ground && jump_pressed && !self.doublejumped && (!self.jumped || self.jump_time < DOUBLE_JUMP_TIMEOUT) {
+            self.doublejumped = true;
+            self.state.velocity += self.state.up * JUMP_SPEED;
+        }
+
+        if self.on_ground {
+            self.step_ground(controls, boosting);
+        } else {
+            if self.holding_jump && self.jump_time < JUMP_MAX_DURATION {
+                self.state.velocity += self.state.up * (JUMP_ACC * SIMULATION_DT);
+            }
+
+            let air_controls = AerialControls {
+                throttle: controls.throttle,
+                boost: boosting,
+                pitch: controls.pitch,
+                yaw: controls.yaw,
+                roll: controls.roll,
+            };
+
+            self.state.step(&air_controls, self.gravity, self.mutators.boost_accel);
+            self.jump_time += SIMULATION_DT;
+
+            if self.state.location.z < CAR_REST_HEIGHT && self.state.velocity.z < 0. {
+                self.land();
+            }
+        }
+
+        self.time += SIMULATION_DT;
+    }
+
+    fn step_ground(&mut self, controls: &ControllerState, boosting: bool) {
+        let v = self.state.velocity.dot(self.state.forward);
+        let accel = ground_acceleration(v, controls.throttle, boosting, controls.handbrake, self.mutators);
+
+        let mut new_v = v + accel * SIMULATION_DT;
+
+        // braking and coasting stop the car instead of reversing it
+        if !boosting && controls.throttle * v <= 0. && new_v * v < 0. {
+            new_v = 0.;
+        }
+
+        new_v = new_v.clamp(MIN_SPEED, MAX_SPEED);
+
+        let radius = if controls.handbrake { powerslide_turn_radius(new_v) } else { turn_radius(new_v) };
+        let yaw_rate = controls.steer.clamp(-1., 1.) * new_v / radius;
+
+        let rotation = Quat::from_axis_angle(self.state.up.into(), yaw_rate * SIMULATION_DT);
+        self.state.forward = rotation.mul_vec3a(self.state.forward).normalize();
+        self.state.right = self.state.up.cross(self.state.forward).normalize();
+
+        self.state.angular_velocity = self.state.up * yaw_rate;
+        self.state.velocity = self.state.forward * new_v;
+        self.state.location += self.state.velocity * SIMULATION_DT;
+    }
+
+    /// Convert the simulated car into what it would look like in a packet
+    pub fn get_game_car(&self, hitbox: Hitbox, hitbox_offset: Vec3A) -> GameCar {
+        let [forward, right, up] = [self.state.forward, self.state.right, self.state.up];
+
+        GameCar {
+            physics: GamePhysics {
+                location: self.state.location.into(),
+                velocity: self.state.velocity.into(),
+                angular_velocity: self.state.angular_velocity.into(),
+                rotation: GameRot {
+                    pitch: forward.z.clamp(-1., 1.).asin(),
+                    yaw: forward.y.atan2(forward.x),
+                    roll: (-right.z).atan2(up.z),
+                },
+            },
+            hitbox,
+            hitbox_offset: GameVec::from(hitbox_offset),
+            boost: self.boost.clamp(0., 100.) as u8,
+            jumped: self.jumped,
+            double_jumped: self.doublejumped,
+            is_demolished: false,
+            has_wheel_contact: self.on_ground,
+        }
+    }
+
+    /// Put the car back on its wheels on the floor
+    fn land(&mut self) {
+        let forward = flatten(self.state.forward)
+            .try_normalize()
+            .unwrap_or_else(|| flatten(self.state.velocity).normalize_or_zero());
+
+        self.state.location.z = CAR_REST_HEIGHT;
+        self.state.velocity.z = 0.;
+        self.state.angular_velocity = Vec3A::ZERO;
+        self.state.forward = forward;
+        self.state.up = Vec3A::Z;
+        self.state.right = self.state.up.cross(forward);
+
+        self.on_ground = true;
+        self.jumped = false;
+        self.doublejumped = false;
+        self.holding_jump = false;
+        self.jump_time = 0.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CarSim;
+    use crate::{car::get_one, constants::*, pytypes::ControllerState, Mutators};
+    use glam::Vec3A;
+
+    #[test]
+    pub fn matches_max_speed() {
+        let car = get_one();
+        let mut sim = CarSim::from(&car, Vec3A::new(0., 0., -650.), Mutators::new(), 0.);
+        let controls = ControllerState {
+            throttle: 1.,
+            boost: true,
+            ..Default::default()
+        };
+
+        for i in 1..120 {
+            sim.step(&controls);
+            assert!((sim.state.velocity.length() - car.max_speed[i]).abs() < 20., "tick {i}");
+        }
+    }
+
+    #[test]
+    pub fn jump_lands() {
+        let car = get_one();
+        let mut sim = CarSim::from(&car, Vec3A::new(0., 0., -650.), Mutators::new(), 0.);
+        let jump = ControllerState { jump: true, ..Default::default() };
+
+        for _ in 0..24 {
+            sim.step(&jump);
+        }
+
+        assert!(!sim.on_ground);
+        assert!(sim.state.location.z > 60.);
+
+        for _ in 0..240 {
+            sim.step(&ControllerState::default());
+        }
+
+        assert!(sim.on_ground);
+        assert!((sim.state.location.z - CAR_REST_HEIGHT).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    pub fn stationary_car_stays_stationary() {
+        let mut car = get_one();
+        car.velocity = Vec3A::ZERO;
+
+        let mut sim = CarSim::from(&car, Vec3A::new(0., 0., -650.), Mutators::new(), 0.);
+        let handbrake = ControllerState {
+            handbrake: true,
+            ..Default::default()
+        };
+
+        for controls in [ControllerState::default(), handbrake] {
+            for _ in 0..60 {
+                sim.step(&controls);
+            }
+
+            assert!(sim.state.velocity.length() < f32::EPSILON);
+            assert!(sim.state.location.distance(car.location) < f32::EPSILON);
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use pyo3::exceptions;
+use pyo3::{exceptions, PyErr};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
 
 pub const MAX_SPEED: f32 = 2300.;
 pub const MAX_SPEED_NO_BOOST: f32 = 1410.;
@@ -19,7 +20,88 @@ pub const END_THROTTLE_ACCEL_B: f32 = 160.;
 pub const AERIAL_THROTTLE_ACCEL: f32 = 100. * (2. / 3.);
 pub const BOOST_ACCEL: f32 = 991. + 2. / 3.;
 pub const AERIAL_START_BOOST_ANGLE: f32 = 0.5;
+pub const AERIAL_SIM_TOLERANCE: f32 = 75.;
 
+pub const ROLL_TORQUE: f32 = 400.;
+pub const PITCH_TORQUE: f32 = 130.;
+pub const YAW_TORQUE: f32 = 95.;
+pub const ROLL_DAMPING: f32 = -50.;
+pub const PITCH_DAMPING: f32 = -30.;
+pub const YAW_DAMPING: f32 = -20.;
+pub const MAX_ANGULAR_SPEED: f32 = 5.5;
+
+pub const ARENA_SIDE_WALL: f32 = 4096.;
+pub const ARENA_BACK_WALL: f32 = 5120.;
+pub const ARENA_CEILING: f32 = 2044.;
+pub const GOAL_HALF_WIDTH: f32 = 893.;
+pub const GOAL_HEIGHT: f32 = 642.;
+/// How far the center of a car is from the surface it's driving on
+pub const CAR_REST_HEIGHT: f32 = 17.;
+/// How far the collision mesh has to push a probe before it counts as hitting the arena
+pub const ARENA_HIT_TOLERANCE: f32 = 0.1;
+
+pub const CAR_MASS: f32 = 180.;
+pub const BALL_MASS: f32 = 30.;
+pub const MAX_BALL_SPEED: f32 = 6000.;
+/// The vertical part of the extra impulse that Psyonix adds to car-ball touches is scaled down by this
+pub const TOUCH_Z_SCALE: f32 = 0.35;
+/// How much of the part of the extra impulse along the car's nose is taken away
+pub const TOUCH_FORWARD_SCALE: f32 = 0.35;
+/// (relative speed, impulse scale) pairs for the extra impulse that Psyonix adds to car-ball touches
+pub const TOUCH_IMPULSE_SCALES: [(f32, f32); 4] = [(0., 0.65), (500., 0.65), (2300., 0.55), (4600., 0.3)];
+/// The soonest after a touch that we'll plan another one
+pub const FOLLOW_UP_MIN_TIME: f32 = 0.2;
+pub const DEFAULT_FOLLOW_UP_TIME: f32 = 2.;
+
+/// How far the ball can be from resting on the car's roof and still count as being on it
+pub const DRIBBLE_ROOF_TOLERANCE: f32 = 30.;
+/// How fast the ball can be bouncing on the car's roof and still count as being on it
+pub const DRIBBLE_MAX_BOUNCE_SPEED: f32 = 250.;
+/// A ball rolling on the roof only speeds up by 2/7 of the car's acceleration, so it rolls back by the other 5/7
+pub const DRIBBLE_ROLLING_SLIP: f32 = 5. / 7.;
+/// How far ahead of the center of the roof the ball is kept
+pub const DRIBBLE_CARRY_OFFSET: f32 = 10.;
+/// How much faster the car tries to go for every unit that the ball is ahead of where it should be
+pub const DRIBBLE_OFFSET_GAIN: f32 = 8.;
+/// How much faster the car tries to go for every unit per second that the ball is rolling forwards
+pub const DRIBBLE_VELOCITY_GAIN: f32 = 1.;
+/// How much the car steers for every unit that the ball is off to the side
+pub const DRIBBLE_STEER_GAIN: f32 = 0.02;
+pub const DRIBBLE_LOOK_AHEAD: f32 = 1000.;
+pub const DRIBBLE_PREDICTION_TIME: f32 = 2.;
+/// How far the car has pitched forward when the back of its roof hits the ball in a flick
+pub const DRIBBLE_FLICK_PITCH: f32 = FRAC_PI_4;
+/// How much speed the front dodge of a flick adds
+pub const DRIBBLE_FLICK_SPEED: f32 = 500.;
+/// How close the ball has to be to the target for a flick to make it there
+pub const DRIBBLE_FLICK_RANGE: f32 = 2500.;
+/// How close a defender in front of the car has to be to flick over it
+pub const DRIBBLE_DEFENDER_RANGE: f32 = 1000.;
+/// The cosine of how far off to the side of the car a defender can be and still be in front of it
+pub const DRIBBLE_DEFENDER_CONE: f32 = 0.5;
+/// How far ahead to look for where the defender will be, which is about how long a flick takes
+pub const DRIBBLE_DEFENDER_REACTION_TIME: f32 = 0.5;
+
+/// How far the car can be from a kickoff spawn and still be on it
+pub const KICKOFF_SPAWN_TOLERANCE: f32 = 50.;
+/// How fast the car can be going and still be waiting on its spawn for the kickoff
+pub const KICKOFF_MAX_SPAWN_SPEED: f32 = 10.;
+/// How much speed the front dodge into the ball adds
+pub const KICKOFF_DODGE_SPEED: f32 = 500.;
+/// How long the car is stuck in the dodge, without being able to speed up
+pub const KICKOFF_DODGE_DURATION: f32 = 0.65;
+/// How long after jumping on a kickoff the car starts dodging
+pub const KICKOFF_DODGE_DELAY: f32 = 0.1;
+/// How long into the dodge the car can touch the ball and still hit it with the dodge
+pub const KICKOFF_DODGE_CONTACT_TIMES: std::ops::Range<f32> = 0.15..0.6;
+/// How far from the ball most bots dodge into it on a kickoff
+pub const KICKOFF_TYPICAL_DODGE_DISTANCE: f32 = 700.;
+/// How far to the side of the center of the ball the car aims on a diagonal or off-center kickoff
+pub const KICKOFF_TARGET_OFFSET: f32 = 30.;
+/// How much sooner a car has to get to the ball to win the kickoff instead of it being a 50/50
+pub const KICKOFF_WIN_MARGIN: f32 = 0.05;
+/// How long a kickoff is simulated before giving up on the car getting to the ball, which is much longer than any standard kickoff takes
+pub const KICKOFF_MAX_TIME: f32 = 4.;
 pub const MIN_BOOST_CONSUMPTION: f32 = BOOST_CONSUMPTION * MIN_BOOST_TIME;
 pub const BOOST_CONSUMPTION_DT: f32 = BOOST_CONSUMPTION * SIMULATION_DT;
 pub const BRAKE_ACC_DT: f32 = BRAKE_ACC * SIMULATION_DT;
@@ -27,13 +109,30 @@ pub const BRAKE_ACC_DT: f32 = BRAKE_ACC * SIMULATION_DT;
 pub const BRAKE_COAST_TRANSITION: f32 = -(0.45 * BRAKE_ACC + 0.55 * COAST_ACC);
 pub const COASTING_THROTTLE_TRANSITION: f32 = -0.5 * COAST_ACC;
 
+pub const POWERSLIDE_CURVATURE_SCALE: f32 = 2.;
+pub const POWERSLIDE_DECEL: f32 = 1200.;
+pub const POWERSLIDE_MIN_SPEED: f32 = 500.;
+pub const POWERSLIDE_MIN_ANGLE: f32 = FRAC_PI_2;
+
+pub const BRAKING_TURN_SPEEDS: [f32; 3] = [1500., 1000., 500.];
+
+/// The widest that a point target's cone can be to either side of the point
+///
+/// At a quarter turn, the edges of the cone point in opposite directions, and past it they swap sides
+pub const MAX_TOLERANCE_ANGLE: f32 = FRAC_PI_2 - 0.01;
+
 pub const REACTION_TIME: f32 = 0.04;
 pub const STEER_REACTION_TIME: f32 = 0.25;
+pub const POWERSLIDE_STEER_RATIO: f32 = 1.5;
 
 pub const JUMP_MAX_DURATION: f32 = 0.2;
 pub const JUMP_SPEED: f32 = 291. + (2. / 3.);
 pub const JUMP_ACC: f32 = 1458. + (1. / 3.);
 pub const DOUBLE_JUMP_DURATION: f32 = JUMP_MAX_DURATION + SIMULATION_DT * 2.;
+/// How long after the first jump the second jump can be used
+pub const DOUBLE_JUMP_TIMEOUT: f32 = 1.25;
+/// When the second jump of a double jump aerial can happen, relative to the first jump
+pub const DOUBLE_JUMP_DELAYS: [f32; 4] = [DOUBLE_JUMP_DURATION, 0.45, 0.75, 1.05];
 
 pub type NoGamePyErr = exceptions::PyNameError;
 pub const NO_GAME_ERR: &str = "GAME is unset. Call a function like load_soccar first.";
@@ -55,6 +154,94 @@ pub type BadAccelerationPyErr = exceptions::PyAssertionError;
 pub const BAD_ACCELERATION_ERR: &str = "Acceleration is slower than expected.";
 pub type StrayedFromPathPyErr = exceptions::PyAssertionError;
 pub const STRAYED_FROM_PATH_ERR: &str = "Car has strayed from the path.";
+pub type NotAirShotPyErr = exceptions::PyTypeError;
+pub const NOT_AIR_SHOT_ERR: &str = "Specified target's shot isn't an aerial.";
+pub type UnknownShapePyErr = exceptions::PyValueError;
+pub const UNKNOWN_SHAPE_ERR: &str = "The ball's collision shape type is unknown.";
+pub type NoFollowUpPyErr = exceptions::PyValueError;
+pub const NO_FOLLOW_UP_ERR: &str = "The car can't touch the ball again after the shot.";
+pub type NotRecordingPyErr = exceptions::PyAssertionError;
+pub const NOT_RECORDING_ERR: &str = "Nothing is being recorded. Call start_recording first.";
+pub type ReplayFilePyErr = exceptions::PyIOError;
+pub const REPLAY_FILE_ERR: &str = "Couldn't read or write the replay file.";
+pub type BadReplayPyErr = exceptions::PyValueError;
+pub const BAD_REPLAY_ERR: &str = "The replay file is corrupted or from a different version.";
+pub type NotDribblingPyErr = exceptions::PyAssertionError;
+pub const NOT_DRIBBLING_ERR: &str = "The ball isn't on the car's roof.";
+pub type BadAreaPyErr = exceptions::PyValueError;
+pub const BAD_AREA_ERR: &str = "The target area needs at least 3 corners.";
+pub type BadTimePyErr = exceptions::PyValueError;
+pub const BAD_TIME_ERR: &str = "The shot is after the end of the ball prediction.";
+pub type NotKickoffPyErr = exceptions::PyAssertionError;
+pub const NOT_KICKOFF_ERR: &str = "The car isn't on a kickoff spawn.";
+pub type NotSoccarPyErr = exceptions::PyAssertionError;
+pub const NOT_SOCCAR_ERR: &str = "Kickoffs can only be planned in the standard soccar arena. Call load_soccar first.";
+pub type SameCarPyErr = exceptions::PyValueError;
+pub const SAME_CAR_ERR: &str = "The car and the opponent can't be the same car.";
+#[cfg(feature = "serde")]
+pub type SerializePyErr = exceptions::PyValueError;
+#[cfg(feature = "serde")]
+pub const SERIALIZE_ERR: &str = "Couldn't serialize the analyzer's state.";
+
+/// Errors that can be returned to Python, which can also be handled in Rust without needing Python
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LibError {
+    NoGame,
+    NoCar,
+    NoSlices,
+    NoTarget,
+    NoShot,
+    BallChanged,
+    NoShotSelected,
+    NoTimeRemaining,
+    BadAcceleration,
+    StrayedFromPath,
+    NotAirShot,
+    UnknownShape,
+    NoFollowUp,
+    NotRecording,
+    ReplayFile,
+    BadReplay,
+    BadArea,
+    NotDribbling,
+    NotKickoff,
+    NotSoccar,
+    BadTime,
+    SameCar,
+    #[cfg(feature = "serde")]
+    Serialize,
+}
+
+impl From<LibError> for PyErr {
+    fn from(error: LibError) -> Self {
+        match error {
+            LibError::NoGame => PyErr::new::<NoGamePyErr, _>(NO_GAME_ERR),
+            LibError::NoCar => PyErr::new::<NoCarPyErr, _>(NO_CAR_ERR),
+            LibError::NoSlices => PyErr::new::<NoSlicesPyErr, _>(NO_SLICES_ERR),
+            LibError::NoTarget => PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR),
+            LibError::NoShot => PyErr::new::<NoShotPyErr, _>(NO_SHOT_ERR),
+            LibError::BallChanged => PyErr::new::<BallChangedPyErr, _>(BALL_CHANGED_ERR),
+            LibError::NoShotSelected => PyErr::new::<NoShotSelectedPyErr, _>(NO_SHOT_SELECTED_ERR),
+            LibError::NoTimeRemaining => PyErr::new::<NoTimeRemainingPyErr, _>(NO_TIME_REMAINING_ERR),
+            LibError::BadAcceleration => PyErr::new::<BadAccelerationPyErr, _>(BAD_ACCELERATION_ERR),
+            LibError::StrayedFromPath => PyErr::new::<StrayedFromPathPyErr, _>(STRAYED_FROM_PATH_ERR),
+            LibError::NotAirShot => PyErr::new::<NotAirShotPyErr, _>(NOT_AIR_SHOT_ERR),
+            LibError::UnknownShape => PyErr::new::<UnknownShapePyErr, _>(UNKNOWN_SHAPE_ERR),
+            LibError::NoFollowUp => PyErr::new::<NoFollowUpPyErr, _>(NO_FOLLOW_UP_ERR),
+            LibError::NotRecording => PyErr::new::<NotRecordingPyErr, _>(NOT_RECORDING_ERR),
+            LibError::ReplayFile => PyErr::new::<ReplayFilePyErr, _>(REPLAY_FILE_ERR),
+            LibError::BadReplay => PyErr::new::<BadReplayPyErr, _>(BAD_REPLAY_ERR),
+            LibError::BadArea => PyErr::new::<BadAreaPyErr, _>(BAD_AREA_ERR),
+            LibError::NotDribbling => PyErr::new::<NotDribblingPyErr, _>(NOT_DRIBBLING_ERR),
+            LibError::NotKickoff => PyErr::new::<NotKickoffPyErr, _>(NOT_KICKOFF_ERR),
+            LibError::NotSoccar => PyErr::new::<NotSoccarPyErr, _>(NOT_SOCCAR_ERR),
+            LibError::BadTime => PyErr::new::<BadTimePyErr, _>(BAD_TIME_ERR),
+            LibError::SameCar => PyErr::new::<SameCarPyErr, _>(SAME_CAR_ERR),
+            #[cfg(feature = "serde")]
+            LibError::Serialize => PyErr::new::<SerializePyErr, _>(SERIALIZE_ERR),
+        }
+    }
+}
 
 pub const STICKY_FORCE: f32 = -325.;
 pub const STICKY_TIMER: f32 = SIMULATION_DT * 3.;
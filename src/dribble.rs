@@ -0,0 +1,259 @@
+use crate::{
+    car::Car,
+    car_sim::ground_acceleration,
+    constants::*,
+    follow_up::touch_ball,
+    ground::{pure_pursuit_steer, speed_controller},
+    utils::{flatten, PostCorrection},
+    Mutators,
+};
+use glam::Vec3A;
+use rl_ball_sym::simulation::ball::Ball;
+
+/// Where the ball is on the car's roof, relative to the center of the roof and in the car's local coordinates
+#[derive(Clone, Copy, Debug)]
+pub struct DribbleState {
+    pub offset: Vec3A,
+    pub velocity: Vec3A,
+}
+
+impl DribbleState {
+    /// Get the state of the ball on the car's roof, or None if it isn't resting on the roof
+    pub fn from(car: &Car, ball: &Ball) -> Option<Self> {
+        if car.airborne || car.demolished {
+            return None;
+        }
+
+        let roof_center = car.hitbox_offset + Vec3A::new(0., 0., car.hitbox.height / 2. + ball.radius());
+        let offset = car.localize(ball.location - car.location) - roof_center;
+        let velocity = car.localize(ball.velocity - car.velocity);
+
+        if offset.z.abs() > DRIBBLE_ROOF_TOLERANCE || velocity.z.abs() > DRIBBLE_MAX_BOUNCE_SPEED {
+            return None;
+        }
+
+        let state = Self { offset, velocity };
+        state.is_on_roof(car).then_some(state)
+    }
+
+    #[inline]
+    fn is_on_roof(&self, car: &Car) -> bool {
+        self.offset.x.abs() < car.hitbox.length / 2. && self.offset.y.abs() < car.hitbox.width / 2.
+    }
+
+    /// Step the ball along the roof while the car speeds up by `acceleration`
+    pub fn step(&mut self, acceleration: f32, dt: f32) {
+        // a ball rolling on a surface only gets some of the surface's acceleration, so it rolls back by the rest
+        self.velocity.x -= acceleration * DRIBBLE_ROLLING_SLIP * dt;
+        self.offset += flatten(self.velocity) * dt;
+    }
+
+    /// How long until the ball rolls off of the roof if the car keeps speeding up by `acceleration`, or None if it stays on for the whole time
+    pub fn fall_time(mut self, car: &Car, acceleration: f32, max_time: f32) -> Option<f32> {
+        let mut time = 0.;
+
+        while time < max_time {
+            self.step(acceleration, SIMULATION_DT);
+            time += SIMULATION_DT;
+
+            if !self.is_on_roof(car) {
+                return Some(time);
+            }
+        }
+
+        None
+    }
+}
+
+/// If flicking the ball now would send it between the target's posts
+///
+/// The flick is a jump and a front dodge that hits the ball with the back of the roof as the car pitches forward
+fn flick_scores(car: &Car, ball: &Ball, post_info: &PostCorrection, gravity: f32) -> bool {
+    let normal = car.up * DRIBBLE_FLICK_PITCH.cos() + car.forward * DRIBBLE_FLICK_PITCH.sin();
+    let car_velocity = car.velocity + car.up * JUMP_SPEED + car.forward * DRIBBLE_FLICK_SPEED;
+    let (ball_velocity, _) = touch_ball(ball.velocity, car_velocity, normal, car.forward);
+
+    // find when the ball crosses the line between the posts
+    let left = flatten(post_info.target_left);
+    let left_to_right = flatten(post_info.target_right) - left;
+    let line_normal = left_to_right.cross(Vec3A::Z);
+
+    let approach_speed = flatten(ball_velocity).dot(line_normal);
+    if approach_speed.abs() < f32::EPSILON {
+        return false;
+    }
+
+    let time = (left - flatten(ball.location)).dot(line_normal) / approach_speed;
+    if time <= 0. {
+        return false;
+    }
+
+    let crossing = ball.location + ball_velocity * time + Vec3A::new(0., 0., 0.5 * gravity * time * time);
+    let between_posts = (0. ..=1.).contains(&((flatten(crossing) - left).dot(left_to_right) / left_to_right.length_squared()));
+
+    between_posts && post_info.window.map_or(true, |(_, top)| crossing.z <= top)
+}
+
+/// If the defender will be close enough in front of the car to challenge soon
+fn defender_is_challenging(car: &Car, defender: &Car) -> bool {
+    if defender.demolished {
+        return false;
+    }
+
+    let car_to_defender = flatten(defender.location - car.location);
+    let closing_velocity = flatten(defender.velocity - car.velocity);
+
+    // a defender that's driving away isn't going to challenge, no matter how close it is
+    if car_to_defender.dot(closing_velocity) >= 0. {
+        return false;
+    }
+
+    let future_car_to_defender = car_to_defender + closing_velocity * DRIBBLE_DEFENDER_REACTION_TIME;
+
+    future_car_to_defender.length() < DRIBBLE_DEFENDER_RANGE && car.forward.dot(future_car_to_defender.normalize_or_zero()) > DRIBBLE_DEFENDER_CONE
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DribblePlan {
+    pub ball_offset: Vec3A,
+    pub throttle: f32,
+    pub steer: f32,
+    pub boost: bool,
+    pub flick: bool,
+    pub fall_time: Option<f32>,
+}
+
+/// Carry the ball towards the target
+///
+/// `post_info` is the target's location for the ball on the roof, or None to carry the ball straight ahead
+pub fn dribble_controls(
+    car: &Car,
+    ball: &Ball,
+    state: DribbleState,
+    post_info: Option<PostCorrection>,
+    defender: Option<&Car>,
+    mutators: Mutators,
+    gravity: f32,
+) -> DribblePlan {
+    let direction = post_info.map_or(car.forward, |post_info| post_info.get_shot_vector_target(car.location, ball.location));
+
+    // if the ball is ahead of where it should be, the car has to speed up to get back under it
+    let speed = car.local_velocity.x;
+    let target_speed = speed + (state.offset.x - DRIBBLE_CARRY_OFFSET) * DRIBBLE_OFFSET_GAIN + state.velocity.x * DRIBBLE_VELOCITY_GAIN;
+    let (throttle, boost) = speed_controller(speed, target_speed.clamp(0., MAX_SPEED), mutators.usable_boost(car.boost));
+
+    // turn towards the target, and under the ball if it's rolling off to the side
+    let (steer, _) = pure_pursuit_steer(car, car.location + flatten(direction) * DRIBBLE_LOOK_AHEAD);
+    let steer = (steer + (state.offset.y + state.velocity.y * STEER_REACTION_TIME) * DRIBBLE_STEER_GAIN).clamp(-1., 1.);
+
+    let flick_scores = post_info.map_or(false, |post_info| {
+        let target = (post_info.target_left + post_info.target_right) / 2.;
+
+        post_info.fits && !post_info.avoid && flatten(target).distance(flatten(ball.location)) < DRIBBLE_FLICK_RANGE && flick_scores(car, ball, &post_info, gravity)
+    });
+
+    // flick over a defender that's about to challenge
+    let flick_beats_defender = defender.map_or(false, |defender| defender_is_challenging(car, defender));
+
+    DribblePlan {
+        ball_offset: state.offset,
+        throttle,
+        steer,
+        boost,
+        flick: flick_scores || flick_beats_defender,
+        fall_time: state.fall_time(car, ground_acceleration(speed, throttle, boost, false, mutators), DRIBBLE_PREDICTION_TIME),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dribble_controls, DribbleState};
+    use crate::{
+        car::{get_one, Car},
+        utils::PostCorrection,
+        Mutators,
+    };
+    use glam::Vec3A;
+    use rl_ball_sym::simulation::ball::Ball;
+
+    const GRAVITY: f32 = -650.;
+
+    fn ball_on_roof(offset: f32) -> (Car, Ball) {
+        let mut car = get_one();
+        car.velocity = car.forward * 1000.;
+        car.calculate_local_values();
+
+        let mut ball = Ball::initialize_soccar();
+        let roof = car.location + car.forward * (car.hitbox_offset.x + offset) + car.up * (car.hitbox_offset.z + car.hitbox.height / 2. + ball.radius());
+        ball.update(0., roof, car.velocity, Vec3A::ZERO);
+
+        (car, ball)
+    }
+
+    #[test]
+    pub fn carry_ball_forwards() {
+        let (car, ball) = ball_on_roof(40.);
+        let state = DribbleState::from(&car, &ball).unwrap();
+
+        // the ball is ahead of the center of the roof, so the car has to speed up to stay under it
+        let controls = dribble_controls(&car, &ball, state, None, None, Mutators::new(), GRAVITY);
+        assert!(controls.throttle > 0.);
+        assert!(!controls.flick);
+
+        // way ahead of the car isn't a dribble
+        let (car, ball) = ball_on_roof(200.);
+        assert!(DribbleState::from(&car, &ball).is_none());
+    }
+
+    #[test]
+    pub fn flick_at_goal() {
+        let (car, ball) = ball_on_roof(0.);
+        let state = DribbleState::from(&car, &ball).unwrap();
+
+        // a goal right in front of the car
+        let goal_center = car.location + car.forward * 1500.;
+        let post_info = PostCorrection::from(ball.location, ball.radius(), goal_center - car.right * 800., goal_center + car.right * 800.);
+
+        let controls = dribble_controls(&car, &ball, state, Some(post_info), None, Mutators::new(), GRAVITY);
+        assert!(controls.flick);
+    }
+
+    #[test]
+    pub fn dont_flick_wide_of_goal() {
+        let (car, ball) = ball_on_roof(0.);
+        let state = DribbleState::from(&car, &ball).unwrap();
+
+        // the goal is close, but off to the side, so the flicked ball would go past it
+        let goal_center = car.location + car.forward * 1200. + car.right * 1200.;
+        let goal_line = (car.forward - car.right).normalize();
+        let post_info = PostCorrection::from(ball.location, ball.radius(), goal_center + goal_line * 800., goal_center - goal_line * 800.);
+
+        let controls = dribble_controls(&car, &ball, state, Some(post_info), None, Mutators::new(), GRAVITY);
+        assert!(!controls.flick);
+    }
+
+    #[test]
+    pub fn flick_over_defender() {
+        let (car, ball) = ball_on_roof(0.);
+        let state = DribbleState::from(&car, &ball).unwrap();
+
+        // a defender in front of the car, driving at it
+        let mut defender = get_one();
+        defender.location = car.location + car.forward * 1200.;
+        defender.velocity = -car.forward * 1000.;
+
+        let controls = dribble_controls(&car, &ball, state, None, Some(&defender), Mutators::new(), GRAVITY);
+        assert!(controls.flick);
+
+        // the same defender driving away isn't a challenge
+        let mut retreating = defender.clone();
+        retreating.velocity = car.forward * 1500.;
+        let controls = dribble_controls(&car, &ball, state, None, Some(&retreating), Mutators::new(), GRAVITY);
+        assert!(!controls.flick);
+
+        // and neither is one that's been demolished
+        defender.demolished = true;
+        let controls = dribble_controls(&car, &ball, state, None, Some(&defender), Mutators::new(), GRAVITY);
+        assert!(!controls.flick);
+    }
+}
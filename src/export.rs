@@ -0,0 +1,62 @@
+use crate::{car::Car, pytypes::BallSlice, shot::Target, Mutators, BALL_STRUCT, CARS, GAME_TIME, GRAVITY, MUTATORS, TARGETS};
+use glam::Vec3A;
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of everything the analyzer knows about the game, for logging and offline analysis
+#[derive(Serialize, Deserialize)]
+pub struct AnalyzerState {
+    pub game_time: f32,
+    pub gravity: Vec3A,
+    pub mutators: Mutators,
+    pub ball_prediction: Vec<BallSlice>,
+    pub cars: Vec<Car>,
+    pub targets: Vec<Option<Target>>,
+}
+
+impl AnalyzerState {
+    pub fn get() -> Self {
+        Self {
+            game_time: *GAME_TIME.read().unwrap(),
+            gravity: *GRAVITY.read().unwrap(),
+            mutators: *MUTATORS.read().unwrap(),
+            ball_prediction: BALL_STRUCT.read().unwrap().iter().copied().map(BallSlice::from).collect(),
+            cars: CARS.read().unwrap().iter().cloned().collect(),
+            targets: TARGETS.read().unwrap().iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnalyzerState;
+    use crate::{
+        car::get_one,
+        shot::{GroundBasedShot, Options, Target},
+        Mutators,
+    };
+    use glam::Vec3A;
+
+    #[test]
+    pub fn state_round_trip() {
+        let mut target = Target::new_any(0, Options::default());
+        target.shot = Some(GroundBasedShot::default().into());
+
+        let state = AnalyzerState {
+            game_time: 1.,
+            gravity: Vec3A::new(0., 0., -650.),
+            mutators: Mutators::new(),
+            ball_prediction: Vec::new(),
+            cars: vec![get_one()],
+            targets: vec![Some(target), None],
+        };
+
+        let json: AnalyzerState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        let bincode: AnalyzerState = bincode::deserialize(&bincode::serialize(&state).unwrap()).unwrap();
+
+        for copy in [json, bincode] {
+            assert_eq!(copy.cars[0].max_speed, state.cars[0].max_speed);
+            assert_eq!(copy.targets.len(), 2);
+            assert!(copy.targets[0].as_ref().unwrap().shot.is_some());
+        }
+    }
+}
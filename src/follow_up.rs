@@ -0,0 +1,177 @@
+use crate::{
+    air::{aerial_shot_is_viable, AerialJumpType, AerialTargetInfo},
+    air_sim::{fly_aerial, fly_rest_of_double_jump},
+    car::Car,
+    constants::*,
+    shot::AirBasedShot,
+    utils::flatten,
+    Mutators,
+};
+use glam::Vec3A;
+use rl_ball_sym::simulation::{ball::Ball, game::Game};
+
+/// How much of the relative speed between the car and the ball gets added by Psyonix's extra impulse
+fn touch_impulse_scale(speed: f32) -> f32 {
+    TOUCH_IMPULSE_SCALES
+        .windows(2)
+        .find(|pair| speed < pair[1].0)
+        .map_or(TOUCH_IMPULSE_SCALES[TOUCH_IMPULSE_SCALES.len() - 1].1, |pair| {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            y0 + (y1 - y0) * (speed - x0) / (x1 - x0)
+        })
+}
+
+/// The velocities of the ball and the car right after the car touches the ball
+///
+/// `normal` points from the car into the ball
+pub fn touch_ball(ball_velocity: Vec3A, car_velocity: Vec3A, normal: Vec3A, car_forward: Vec3A) -> (Vec3A, Vec3A) {
+    let relative_velocity = car_velocity - ball_velocity;
+    let closing_speed = relative_velocity.dot(normal);
+
+    if closing_speed <= 0. {
+        return (ball_velocity, car_velocity);
+    }
+
+    // the physics engine's impulse, treating the touch as perfectly inelastic
+    let impulse = closing_speed * BALL_MASS * CAR_MASS / (BALL_MASS + CAR_MASS);
+    let mut new_ball_velocity = ball_velocity + normal * (impulse / BALL_MASS);
+    let new_car_velocity = car_velocity - normal * (impulse / CAR_MASS);
+
+    // the extra impulse that Psyonix adds, which is weaker vertically and along the car's nose
+    let psyonix_direction = Vec3A::new(normal.x, normal.y, normal.z * TOUCH_Z_SCALE);
+    let psyonix_direction = (psyonix_direction - car_forward * (psyonix_direction.dot(car_forward) * TOUCH_FORWARD_SCALE)).normalize_or_zero();
+    let relative_speed = relative_velocity.length().min(TOUCH_IMPULSE_SCALES[TOUCH_IMPULSE_SCALES.len() - 1].0);
+    new_ball_velocity += psyonix_direction * (relative_speed * touch_impulse_scale(relative_speed));
+
+    (new_ball_velocity.clamp_length_max(MAX_BALL_SPEED), new_car_velocity)
+}
+
+/// Fly the aerial to the ball and predict the state of the car and the ball right after the touch
+///
+/// If `flip_reset` is true, the car is assumed to touch the ball with its wheels and get its jump back
+pub fn predict_touch(car: &Car, shot: &AirBasedShot, ball: &Ball, mutators: Mutators, game: &Game, time_remaining: f32, flip_reset: bool) -> (Car, Ball) {
+    // once the car is in the air, any jumps from the ground have already happened, except for the second jump of a double jump
+    let second_jump_pending = shot.jump_type == AerialJumpType::Double && car.airborne && !car.doublejumped && car.air_time < shot.double_jump_delay;
+    let jump_type = match shot.jump_type {
+        AerialJumpType::Secondary if car.can_secondary_jump() => AerialJumpType::Secondary,
+        _ if car.airborne && !second_jump_pending => AerialJumpType::None,
+        jump_type => jump_type,
+    };
+
+    let target_info = AerialTargetInfo {
+        shot_vector: (shot.ball_location - shot.final_target).normalize_or_zero(),
+        jump_type,
+        double_jump_delay: shot.double_jump_delay,
+        final_target: shot.final_target,
+    };
+
+    let (state, boost) = if second_jump_pending {
+        fly_rest_of_double_jump(car, mutators, game.gravity, &target_info, time_remaining)
+    } else {
+        fly_aerial(car, mutators, game.gravity, &target_info, time_remaining)
+    };
+    let (ball_velocity, car_velocity) = touch_ball(ball.velocity, state.velocity, (ball.location - state.location).normalize_or_zero(), state.forward);
+
+    let mut next_ball = *ball;
+    next_ball.velocity = ball_velocity;
+
+    let mut next_car = car.clone();
+    next_car.location = state.location;
+    next_car.velocity = car_velocity;
+    next_car.angular_velocity = state.angular_velocity;
+    next_car.forward = state.forward;
+    next_car.right = state.right;
+    next_car.up = state.up;
+    next_car.boost = boost.max(0.) as u8;
+    next_car.airborne = true;
+    next_car.jumped = car.jumped || jump_type != AerialJumpType::None;
+    next_car.doublejumped = !flip_reset && (car.doublejumped || matches!(jump_type, AerialJumpType::Double | AerialJumpType::Secondary));
+    next_car.has_flip_reset = flip_reset;
+    next_car.air_time = if !second_jump_pending && matches!(jump_type, AerialJumpType::Normal | AerialJumpType::Double) {
+        time_remaining - car.landing_time
+    } else {
+        car.air_time + time_remaining
+    };
+    next_car.calculate_landing_info(game.gravity.z, Some(game));
+
+    (next_car, next_ball)
+}
+
+/// Look for a second aerial touch in the ball's path after the first touch
+///
+/// The first slice of `ball_prediction` is the ball right after the first touch
+pub fn find_follow_up(car: &Car, ball_prediction: &[Ball], mutators: Mutators, gravity: Vec3A) -> Result<(Ball, AerialTargetInfo), LibError> {
+    let start_time = ball_prediction.first().ok_or(LibError::NoFollowUp)?.time;
+
+    for ball in ball_prediction {
+        let time_remaining = ball.time - start_time;
+
+        if time_remaining < FOLLOW_UP_MIN_TIME {
+            continue;
+        }
+
+        let ball_edge = ball.location - flatten(ball.location - car.location).normalize_or_zero() * ball.radius();
+        let shot_vector = (ball_edge - car.location).normalize_or_zero();
+        let target_location = ball_edge - shot_vector * (car.hitbox_offset.x + car.hitbox.length) / 2.;
+
+        if let Ok(target_info) = aerial_shot_is_viable(car, mutators, gravity, target_location, shot_vector, time_remaining, None) {
+            return Ok((*ball, target_info));
+        }
+    }
+
+    Err(LibError::NoFollowUp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{predict_touch, touch_ball};
+    use crate::{air::AerialJumpType, car::get_one, shot::AirBasedShot, Mutators};
+    use glam::Vec3A;
+
+    #[test]
+    pub fn touch_pushes_ball() {
+        let (ball_velocity, car_velocity) = touch_ball(Vec3A::ZERO, Vec3A::new(1000., 0., 0.), Vec3A::X, Vec3A::X);
+
+        assert!(ball_velocity.x > car_velocity.x);
+        assert!(car_velocity.x < 1000.);
+        assert!(ball_velocity.y.abs() < f32::EPSILON);
+    }
+
+    #[test]
+    pub fn off_center_touch_goes_wide() {
+        // the extra impulse is weaker along the car's nose, so the ball goes wider than the contact normal
+        let normal = Vec3A::new(1., 1., 0.).normalize();
+        let (ball_velocity, _) = touch_ball(Vec3A::ZERO, Vec3A::new(1000., 0., 0.), normal, Vec3A::X);
+
+        assert!(ball_velocity.y > ball_velocity.x);
+    }
+
+    #[test]
+    pub fn second_jump_still_pending() {
+        let (game, ball) = rl_ball_sym::compressed::load_soccar();
+
+        let mut car = get_one();
+        car.location.z = 100.;
+        car.velocity = Vec3A::new(0., 0., 500.);
+        car.airborne = true;
+        car.jumped = true;
+        car.air_time = 0.3;
+
+        let mut shot = AirBasedShot {
+            time: 1.,
+            final_target: car.location + car.forward * 500. + Vec3A::new(0., 0., 800.),
+            jump_type: AerialJumpType::Double,
+            double_jump_delay: 0.45,
+            ball_location: car.location + car.forward * 600. + Vec3A::new(0., 0., 850.),
+        };
+
+        let (double_jump_car, _) = predict_touch(&car, &shot, &ball, Mutators::new(), &game, 1., false);
+        assert!(double_jump_car.doublejumped);
+
+        // the same aerial, but with the car's jumps already used
+        shot.jump_type = AerialJumpType::Normal;
+        let (single_jump_car, _) = predict_touch(&car, &shot, &ball, Mutators::new(), &game, 1., false);
+        assert!(!single_jump_car.doublejumped);
+        assert!(double_jump_car.location.z > single_jump_car.location.z);
+    }
+}
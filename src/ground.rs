@@ -1,13 +1,13 @@
 use crate::{
-    car::{throttle_acceleration, Car, FieldRect},
+    car::{curvature, throttle_acceleration, Car, FieldRect},
     constants::*,
-    pytypes::{BasicShotInfo, ShotType},
+    pytypes::{BasicShotInfo, ShotRejection, ShotType},
     utils::*,
     BoostAmount, Mutators,
 };
-use dubins_paths::{DubinsPath, Intermediate, NoPathError, PathType, PosRot, Result as DubinsResult};
+use dubins_paths::{mod2pi, DubinsPath, Intermediate, NoPathError, PathType, PosRot, Result as DubinsResult};
 use glam::Vec3A;
-use std::f32::{consts::E, INFINITY};
+use std::f32::consts::{E, FRAC_PI_2};
 
 /// <https://stackoverflow.com/a/49987361/10930209>
 fn get_turn_exit_tangets(target: Vec3A, circle_center: Vec3A, radius: f32) -> (Vec3A, Vec3A) {
@@ -57,7 +57,7 @@ pub fn angle_2d(vec1: Vec3A, vec2: Vec3A) -> f32 {
 }
 
 pub fn shortest_path_in_validate(q0: PosRot, q1: PosRot, rho: f32, car_field: &FieldRect, max_distance: f32) -> DubinsResult<DubinsPath> {
-    let mut best_cost = INFINITY;
+    let mut best_cost = f32::INFINITY;
     let mut best_path = None;
 
     let intermediate_results = Intermediate::from(q0, q1, rho);
@@ -82,10 +82,53 @@ pub fn shortest_path_in_validate(q0: PosRot, q1: PosRot, rho: f32, car_field: &F
     best_path.ok_or(NoPathError)
 }
 
+/// Get the powerslide arc that turns the car until it faces the target
+///
+/// The path only has a first segment and it should be driven with the handbrake held
+pub fn get_powerslide_turn(q0: PosRot, target: Vec3A, rho: f32, counter_clockwise: bool) -> DubinsResult<DubinsPath> {
+    let (side_angle, path_type) = if counter_clockwise { (FRAC_PI_2, PathType::LSL) } else { (-FRAC_PI_2, PathType::RSR) };
+
+    let (sin, cos) = (q0.rot + side_angle).sin_cos();
+    let circle_center = q0.pos + Vec3A::new(cos, sin, 0.) * rho;
+
+    let center_to_target = flatten(target - circle_center);
+    let b = center_to_target.length();
+
+    if b <= rho {
+        return Err(NoPathError);
+    }
+
+    let d = center_to_target.y.atan2(center_to_target.x);
+    let th = (rho / b).acos();
+
+    // the angle on the circle where the car is and the angle on the circle where the car will face the target
+    let start_angle = q0.rot - side_angle;
+    let turn_angle = if counter_clockwise {
+        mod2pi(d - th - start_angle)
+    } else {
+        mod2pi(start_angle - (d + th))
+    };
+
+    if turn_angle < POWERSLIDE_MIN_ANGLE {
+        return Err(NoPathError);
+    }
+
+    Ok(DubinsPath {
+        qi: q0,
+        rho,
+        type_: path_type,
+        param: [turn_angle, 0., 0.],
+    })
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GroundTargetInfo {
     pub distances: [f32; 4],
     pub path: DubinsPath,
+    /// an optional powerslide turn that gets driven before `path`, which is included in `distances[0]`
+    pub powerslide: Option<DubinsPath>,
+    /// the speed to brake down to before each turn, if the path was made with a tighter turn radius than the car's max speed allows
+    pub max_turn_speed: Option<f32>,
     pub shot_type: ShotType,
     pub jump_time: Option<f32>,
     pub is_forwards: bool,
@@ -107,6 +150,8 @@ impl GroundTargetInfo {
         Self {
             distances,
             path,
+            powerslide: None,
+            max_turn_speed: None,
             shot_type,
             jump_time,
             is_forwards,
@@ -115,7 +160,7 @@ impl GroundTargetInfo {
         }
     }
 
-    pub fn can_reach(&self, car: &Car, max_time: f32, mutators: Mutators) -> Result<f32, ()> {
+    pub fn can_reach(&self, car: &Car, max_time: f32, mutators: Mutators) -> Result<f32, ShotRejection> {
         let is_curved = PathType::CCC.contains(&self.path.type_);
 
         let total_d = self.distances.iter().sum::<f32>();
@@ -127,6 +172,8 @@ impl GroundTargetInfo {
             start..end
         };
 
+        let powerslide_d = self.powerslide.map_or(0., |path| path.length());
+
         let direction = if self.is_forwards { 1. } else { -1. };
 
         let mut d = total_d;
@@ -141,19 +188,34 @@ impl GroundTargetInfo {
             }
 
             if t_r <= 0. {
-                return Err(());
+                return Err(ShotRejection::TooSlow);
             }
 
             let r = d * direction / t_r;
-            let t = r - v;
 
             let distance_traveled = total_d - d;
+            let target_speed = turn_speed_limit(&self.distances, self.max_turn_speed, distance_traveled, is_curved).map_or(r, |limit| r.min(limit));
+            let t = target_speed - v;
+
+            if distance_traveled < powerslide_d {
+                // the handbrake is held, so all we can do is bleed off speed until the turn is done
+                v = (v - POWERSLIDE_DECEL * SIMULATION_DT).max(0.);
+
+                if v < f32::EPSILON {
+                    return Err(ShotRejection::TooSlow);
+                }
+
+                t_r -= SIMULATION_DT;
+                d -= v * SIMULATION_DT;
+                continue;
+            }
+
             let is_middle_straight = !is_curved && middle_range.contains(&distance_traveled);
 
             if t.abs() < 20. {
                 if d <= self.distances[3] + 1. {
                     break;
-                } else if is_middle_straight {
+                } else if is_middle_straight && self.max_turn_speed.is_none() {
                     // skip ahead to the end of the section
                     let final_d = total_d - self.distances[1] - self.distances[0];
                     let delta_d = final_d - d;
@@ -174,26 +236,14 @@ impl GroundTargetInfo {
                 };
 
                 if r > quick_max_speed {
-                    return Err(());
+                    return Err(ShotRejection::TooSlow);
                 }
             } else if MIN_SPEED > r {
-                return Err(());
+                return Err(ShotRejection::TooSlow);
             }
 
             let throttle_accel = throttle_acceleration(v);
-            let (throttle, boost) = {
-                let (mut throttle, mut boost) = get_throttle_and_boost(throttle_accel, b, if v < 0. { -t } else { t });
-
-                if t <= 0. {
-                    boost = false;
-                }
-
-                if v < 0. {
-                    throttle *= -1.;
-                }
-
-                (throttle, boost)
-            };
+            let (throttle, boost) = speed_controller(v, target_speed, b);
             let mut accel = 0.;
 
             if throttle == 0. {
@@ -232,6 +282,64 @@ impl GroundTargetInfo {
     }
 }
 
+/// The fastest speed the car can go at the given distance along the path and still brake in time for the next turn
+pub fn turn_speed_limit(distances: &[f32; 4], max_turn_speed: Option<f32>, distance_traveled: f32, is_curved: bool) -> Option<f32> {
+    let max_turn_speed = max_turn_speed?;
+
+    let second_turn_start = distances[0] + distances[1];
+    if distance_traveled >= second_turn_start + distances[2] {
+        // we're out of the turns, so there's nothing left to brake for
+        return None;
+    }
+
+    if is_curved || distance_traveled < distances[0] || distance_traveled >= second_turn_start {
+        return Some(max_turn_speed);
+    }
+
+    // v^2 = v0^2 + 2ad
+    let distance_to_turn = second_turn_start - distance_traveled;
+    Some((max_turn_speed * max_turn_speed + 2. * BRAKE_ACC * distance_to_turn).sqrt())
+}
+
+/// Pure pursuit steering towards a look-ahead point on the path
+///
+/// Returns the steer and if the turn is so sharp that the handbrake should be held
+pub fn pure_pursuit_steer(car: &Car, look_ahead: Vec3A) -> (f32, bool) {
+    let local_target = car.localize(flatten(look_ahead - car.location));
+    let l_squared = local_target.length_squared();
+
+    if l_squared < f32::EPSILON {
+        return (0., false);
+    }
+
+    // the curvature of the arc that goes through both the car and the look-ahead point
+    let required_curvature = 2. * local_target.y / l_squared;
+
+    let speed = car.local_velocity.x;
+    let max_curvature = curvature(speed.abs().min(MAX_SPEED));
+    let ratio = required_curvature / max_curvature;
+
+    let handbrake = speed > POWERSLIDE_MIN_SPEED && local_target.x > 0. && ratio.abs() > POWERSLIDE_STEER_RATIO;
+
+    (ratio.clamp(-1., 1.), handbrake)
+}
+
+/// Get the throttle and boost that will take the car from the current speed `v` to the required speed `r`
+pub fn speed_controller(v: f32, r: f32, b: f32) -> (f32, bool) {
+    let t = r - v;
+    let (mut throttle, mut boost) = get_throttle_and_boost(throttle_acceleration(v), b, if v < 0. { -t } else { t });
+
+    if t <= 0. {
+        boost = false;
+    }
+
+    if v < 0. {
+        throttle *= -1.;
+    }
+
+    (throttle, boost)
+}
+
 fn get_throttle_and_boost(throttle_accel: f32, b: f32, t: f32) -> (f32, bool) {
     let acceleration = t / REACTION_TIME;
     let throttle_boost_transition = throttle_accel + 0.5 * BOOST_ACCEL;
@@ -249,3 +357,35 @@ fn get_throttle_and_boost(throttle_accel: f32, b: f32, t: f32) -> (f32, bool) {
         (0., false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{get_powerslide_turn, pure_pursuit_steer};
+    use crate::car::get_one;
+    use dubins_paths::PosRot;
+    use glam::Vec3A;
+
+    #[test]
+    pub fn powerslide_faces_target() {
+        let target = Vec3A::new(-500., 1000., 0.);
+        let path = get_powerslide_turn(PosRot::new(Vec3A::ZERO, 0.), target, 200., true).unwrap();
+
+        let endpoint = path.endpoint();
+        let to_target = (target - endpoint.pos).normalize();
+        let (sin, cos) = endpoint.rot.sin_cos();
+
+        assert!(to_target.dot(Vec3A::new(cos, sin, 0.)) > 0.999);
+    }
+
+    #[test]
+    pub fn pure_pursuit_steers_towards_target() {
+        let car = get_one();
+
+        let (steer, handbrake) = pure_pursuit_steer(&car, car.location + car.forward * 1000. + car.right * 200.);
+        assert!(steer > 0.);
+        assert!(!handbrake);
+
+        let (steer, _) = pure_pursuit_steer(&car, car.location + car.forward * 1000. - car.right * 200.);
+        assert!(steer < 0.);
+    }
+}
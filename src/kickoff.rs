@@ -0,0 +1,242 @@
+use crate::{
+    car::Car,
+    constants::*,
+    pytypes::{KickoffOutcome, KickoffSpawn},
+    utils::flatten,
+};
+use glam::Vec3A;
+
+/// The absolute x and y of each standard soccar kickoff spawn
+const SPAWNS: [(KickoffSpawn, f32, f32); 3] = [
+    (KickoffSpawn::Diagonal, 2048., 2560.),
+    (KickoffSpawn::OffCenter, 256., 3840.),
+    (KickoffSpawn::Center, 0., 4608.),
+];
+
+/// Get the kickoff spawn that the car is waiting on, if it's on one
+pub fn get_spawn(car: &Car) -> Option<KickoffSpawn> {
+    if car.airborne || car.velocity.length() > KICKOFF_MAX_SPAWN_SPEED {
+        return None;
+    }
+
+    let location = car.location;
+    SPAWNS
+        .iter()
+        .find_map(|(spawn, x, y)| ((location.x.abs() - x).abs() < KICKOFF_SPAWN_TOLERANCE && (location.y.abs() - y).abs() < KICKOFF_SPAWN_TOLERANCE).then_some(*spawn))
+}
+
+/// If the ball is resting on the center spot, waiting for the kickoff
+#[inline]
+fn is_ball_on_center_spot(ball_location: Vec3A, ball_radius: f32) -> bool {
+    ball_location.distance(Vec3A::new(0., 0., ball_radius)) < KICKOFF_SPAWN_TOLERANCE
+}
+
+#[inline]
+fn speed_at(max_speed: &[f32], time: f32) -> f32 {
+    let index = (time * TPS).round() as usize;
+    max_speed.get(index).or_else(|| max_speed.last()).copied().unwrap_or_default().max(0.)
+}
+
+/// When the car gets to the ball if it boosts straight at it, and jumps to dodge into it at `dodge_time`
+fn arrival_time(max_speed: &[f32], distance: f32, dodge_time: Option<f32>) -> Option<f32> {
+    let dodge = dodge_time.map(|dodge_time| {
+        let start = dodge_time + KICKOFF_DODGE_DELAY;
+        (start, (speed_at(max_speed, start) + KICKOFF_DODGE_SPEED).min(MAX_SPEED))
+    });
+
+    let mut distance_covered = 0.;
+    let mut time = 0.;
+
+    while time < KICKOFF_MAX_TIME {
+        let speed = speed_at(max_speed, time);
+        let speed = match dodge {
+            // the car can't boost any faster while it's flipping
+            Some((start, dodge_speed)) if (start..start + KICKOFF_DODGE_DURATION).contains(&time) => dodge_speed,
+            Some((start, dodge_speed)) if time >= start => speed.max(dodge_speed),
+            _ => speed,
+        };
+
+        distance_covered += speed * SIMULATION_DT;
+        time += SIMULATION_DT;
+
+        if distance_covered >= distance {
+            return Some(time);
+        }
+    }
+
+    None
+}
+
+/// How far the car has driven by the end of each tick if it boosts straight ahead without dodging
+fn distance_curve(max_speed: &[f32]) -> Vec<f32> {
+    (0..(KICKOFF_MAX_TIME * TPS).round() as usize)
+        .scan(0., |distance, tick| {
+            *distance += speed_at(max_speed, tick as f32 * SIMULATION_DT) * SIMULATION_DT;
+            Some(*distance)
+        })
+        .collect()
+}
+
+/// Find when to dodge so the car gets to the ball the soonest, while still being in the dodge when it touches the ball
+///
+/// The car goes at a constant speed while it's flipping, so the contact time for each dodge time comes straight from how far it drove before the dodge
+fn best_dodge(max_speed: &[f32], distance: f32) -> Option<(f32, f32)> {
+    let distances = distance_curve(max_speed);
+    let dodge_delay_ticks = (KICKOFF_DODGE_DELAY * TPS).round() as usize;
+
+    // the car can't dodge after it's already at the ball
+    let latest_dodge_tick = distances.partition_point(|covered| *covered < distance);
+    if latest_dodge_tick == distances.len() {
+        return None;
+    }
+
+    (0..=latest_dodge_tick)
+        .filter_map(|dodge_tick| {
+            let start_tick = dodge_tick + dodge_delay_ticks;
+            let covered = start_tick.checked_sub(1).map_or(Some(0.), |tick| distances.get(tick).copied())?;
+            if covered >= distance {
+                return None;
+            }
+
+            let dodge_speed = (speed_at(max_speed, start_tick as f32 * SIMULATION_DT) + KICKOFF_DODGE_SPEED).min(MAX_SPEED);
+            let contact_ticks = ((distance - covered) / (dodge_speed * SIMULATION_DT)).ceil();
+
+            KICKOFF_DODGE_CONTACT_TIMES
+                .contains(&(contact_ticks * SIMULATION_DT))
+                .then_some((dodge_tick as f32 * SIMULATION_DT, (start_tick as f32 + contact_ticks) * SIMULATION_DT))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// When the car gets to the ball with the kickoff that most bots use, which is to boost straight at the ball and dodge when it gets close
+fn typical_arrival_time(max_speed: &[f32], distance: f32) -> Option<f32> {
+    let dodge_time = arrival_time(max_speed, distance - KICKOFF_TYPICAL_DODGE_DISTANCE, None).unwrap_or(0.);
+    arrival_time(max_speed, distance, Some(dodge_time))
+}
+
+/// How far the front of the car has to drive to touch the ball
+#[inline]
+fn contact_distance(car: &Car, ball_location: Vec3A, ball_radius: f32) -> f32 {
+    flatten(ball_location - car.location).length() - ball_radius - (car.hitbox_offset.x + car.hitbox.length / 2.)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KickoffPlan {
+    pub spawn: KickoffSpawn,
+    pub target: Vec3A,
+    pub boost_time: f32,
+    pub dodge_time: Option<f32>,
+    pub arrival_time: Option<f32>,
+    pub opponent_arrival_time: Option<f32>,
+    pub outcome: KickoffOutcome,
+}
+
+/// Plan the car's kickoff, and how it will go against the opponent's typical kickoff
+///
+/// If there's no opponent, one is assumed to be on the same spawn on the other side of the field
+pub fn plan_kickoff(car: &Car, opponent: Option<&Car>, ball_location: Vec3A, ball_radius: f32) -> Option<KickoffPlan> {
+    if !is_ball_on_center_spot(ball_location, ball_radius) {
+        return None;
+    }
+
+    let spawn = get_spawn(car)?;
+    let distance = contact_distance(car, ball_location, ball_radius);
+
+    let (dodge_time, arrival_time) = match best_dodge(&car.max_speed, distance) {
+        Some((dodge_time, arrival_time)) => (Some(dodge_time), Some(arrival_time)),
+        None => (None, self::arrival_time(&car.max_speed, distance, None)),
+    };
+
+    let opponent_arrival_time = match opponent {
+        Some(opponent) => typical_arrival_time(&opponent.max_speed, contact_distance(opponent, ball_location, ball_radius)),
+        None => typical_arrival_time(&car.max_speed, distance),
+    };
+
+    let outcome = match (arrival_time, opponent_arrival_time) {
+        (Some(time), Some(opponent_time)) if opponent_time - time > KICKOFF_WIN_MARGIN => KickoffOutcome::Win,
+        (Some(time), Some(opponent_time)) if time - opponent_time > KICKOFF_WIN_MARGIN => KickoffOutcome::Lose,
+        (Some(_), Some(_)) => KickoffOutcome::FiftyFifty,
+        (Some(_), None) => KickoffOutcome::Win,
+        (None, _) => KickoffOutcome::Lose,
+    };
+
+    // hit the side of the ball that's closer to the car's spawn so the ball goes across the opponent instead of straight back
+    let target = match spawn {
+        KickoffSpawn::Center => ball_location,
+        KickoffSpawn::Diagonal | KickoffSpawn::OffCenter => ball_location + Vec3A::new(car.location.x.signum() * KICKOFF_TARGET_OFFSET, 0., 0.),
+    };
+
+    // boosting past max speed doesn't do anything
+    let max_speed_time = car
+        .max_speed
+        .iter()
+        .position(|speed| *speed >= MAX_SPEED)
+        .map_or(KICKOFF_MAX_TIME, |index| index as f32 * SIMULATION_DT);
+    let boost_time = arrival_time.map_or(max_speed_time, |time| time.min(max_speed_time));
+
+    Some(KickoffPlan {
+        spawn,
+        target,
+        boost_time,
+        dodge_time,
+        arrival_time,
+        opponent_arrival_time,
+        outcome,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_spawn, plan_kickoff};
+    use crate::{
+        car::{get_one, Car},
+        pytypes::KickoffSpawn,
+    };
+    use glam::Vec3A;
+
+    fn car_at(location: Vec3A) -> Car {
+        let mut car = get_one();
+        car.location = location;
+        car
+    }
+
+    #[test]
+    pub fn kickoff_spawns() {
+        assert_eq!(get_spawn(&car_at(Vec3A::new(-2048., -2560., 17.))), Some(KickoffSpawn::Diagonal));
+        assert_eq!(get_spawn(&car_at(Vec3A::new(256., 3840., 17.))), Some(KickoffSpawn::OffCenter));
+        assert_eq!(get_spawn(&car_at(Vec3A::new(0., -4608., 17.))), Some(KickoffSpawn::Center));
+        assert_eq!(get_spawn(&car_at(Vec3A::new(-3000., 1500., 17.))), None);
+
+        // a car that's already driving or jumping past a spawn isn't waiting on it
+        let mut car = car_at(Vec3A::new(0., -4608., 17.));
+        car.velocity = Vec3A::new(0., 1000., 0.);
+        assert_eq!(get_spawn(&car), None);
+
+        let mut car = car_at(Vec3A::new(0., -4608., 17.));
+        car.airborne = true;
+        assert_eq!(get_spawn(&car), None);
+    }
+
+    #[test]
+    pub fn ball_not_on_center_spot() {
+        let car = car_at(Vec3A::new(-2048., -2560., 17.));
+
+        assert!(plan_kickoff(&car, None, Vec3A::new(0., 0., 92.75), 92.75).is_some());
+        assert!(plan_kickoff(&car, None, Vec3A::new(0., 1000., 92.75), 92.75).is_none());
+        assert!(plan_kickoff(&car, None, Vec3A::new(0., 0., 500.), 92.75).is_none());
+    }
+
+    #[test]
+    pub fn diagonal_kickoff_plan() {
+        let mut car = get_one();
+        car.location = Vec3A::new(-2048., -2560., 17.);
+
+        let plan = plan_kickoff(&car, None, Vec3A::new(0., 0., 92.75), 92.75).unwrap();
+        let arrival_time = plan.arrival_time.unwrap();
+
+        // dodging into the ball is faster than the opponent's typical kickoff, which dodges too early
+        assert!(plan.dodge_time.unwrap() < arrival_time);
+        assert!(arrival_time <= plan.opponent_arrival_time.unwrap());
+        assert!(plan.target.x < 0.);
+    }
+}
@@ -1,11 +1,21 @@
 #![forbid(unsafe_code)]
 
 mod air;
+mod air_sim;
 mod analyzer;
 mod car;
+mod car_sim;
 mod constants;
+mod dribble;
+#[cfg(feature = "serde")]
+mod export;
+mod follow_up;
 mod ground;
+mod kickoff;
 mod pytypes;
+mod render;
+mod replay;
+mod shadow;
 mod shot;
 mod utils;
 
@@ -16,13 +26,14 @@ use constants::*;
 use glam::Vec3A;
 use pyo3::prelude::*;
 use pytypes::*;
+use replay::{Arena, Event};
 use rl_ball_sym::simulation::{
     ball::{Ball, BallPrediction},
     game::Game,
 };
-use shot::{AirBasedShot, GroundBasedShot, Options, Shot, Target};
-use std::sync::RwLock;
-use utils::*;
+use shot::{Options, Shot, Target, TargetLocation};
+use std::{fs, sync::RwLock};
+use utils::PostCorrection;
 
 static CARS: RwLock<ReArr<Car, 8>> = RwLock::new(rearr![]);
 static BALL_STRUCT: RwLock<BallPrediction> = RwLock::new(BallPrediction::new());
@@ -32,13 +43,17 @@ static GAME: RwLock<Option<Game>> = RwLock::new(None);
 static BALL: RwLock<Ball> = RwLock::new(Ball::const_default());
 static MUTATORS: RwLock<Mutators> = RwLock::new(Mutators::new());
 static TARGETS: RwLock<ReArr<Option<Target>, 16>> = RwLock::new(rearr![]);
+static ARENA: RwLock<Option<Arena>> = RwLock::new(None);
+static RECORDING: RwLock<Option<Vec<u8>>> = RwLock::new(None);
+/// The last tick and its prediction time, so a recording can start from the current game state
+static LAST_TICK: RwLock<Option<(replay::Frame, f32)>> = RwLock::new(None);
 
 macro_rules! pynamedmodule {
-    (doc: $doc:literal, name: $name:tt, funcs: [$($func_name:path),*], classes: [$($class_name:ident),*]) => {
+    (doc: $doc:literal, name: $name:tt, funcs: [$($(#[$func_meta:meta])* $func_name:path),*], classes: [$($class_name:ident),*]) => {
         #[doc = $doc]
         #[pymodule]
         fn $name(_py: Python, m: &PyModule) -> PyResult<()> {
-            $(m.add_function(wrap_pyfunction!($func_name, m)?)?);*;
+            $($(#[$func_meta])* m.add_function(wrap_pyfunction!($func_name, m)?)?;)*
             $(m.add_class::<$class_name>()?);*;
             Ok(())
         }
@@ -49,18 +64,26 @@ pynamedmodule!(
     doc: "VirxERLU-RLib is written in Rust with Python bindings to make analyzing the ball prediction struct much faster.",
     name: virx_erlu_rlib,
     funcs: [load_soccer, load_soccar, load_dropshot, load_hoops, load_soccer_throwback, load_soccar_throwback,
-    tick, get_slice, get_slice_index, get_num_ball_slices, set_mutator_settings,
-    new_target, new_any_target, confirm_target, remove_target, print_targets, get_targets_length,
-    get_shot_with_target, get_data_for_shot_with_target],
-    classes: [TargetOptions, ShotType]
+    tick, get_slice, get_slice_index, get_num_ball_slices, get_post_correction, set_mutator_settings,
+    new_target, new_window_target, new_point_target, new_area_target, new_away_target, new_away_from_point_target, new_any_target, confirm_target, remove_target, print_targets, get_targets_length,
+    get_shot_with_target, get_data_for_shot_with_target, get_controls_for_shot, get_follow_up_for_shot, get_render_for_shot, is_dribbling, get_dribble_controls, get_shadow_position, get_kickoff_plan, new_car_sim,
+    start_recording, stop_recording, replay_recording,
+    #[cfg(feature = "serde")] get_state_json, #[cfg(feature = "serde")] get_state_bincode],
+    classes: [TargetOptions, ShotType, ShotRejection, KickoffSpawn, KickoffOutcome]
 );
 
+fn set_game(arena: Arena, game: Game, ball: Ball) {
+    *GAME.write().unwrap() = Some(game);
+    *BALL.write().unwrap() = ball;
+    *ARENA.write().unwrap() = Some(arena);
+
+    replay::record(|| Event::Load(arena));
+}
+
 #[pyfunction]
 fn load_soccar() {
     let (game, ball) = rl_ball_sym::compressed::load_soccar();
-
-    *GAME.write().unwrap() = Some(game);
-    *BALL.write().unwrap() = ball;
+    set_game(Arena::Soccar, game, ball);
 }
 
 #[pyfunction]
@@ -71,25 +94,19 @@ fn load_soccer() {
 #[pyfunction]
 fn load_dropshot() {
     let (game, ball) = rl_ball_sym::compressed::load_dropshot();
-
-    *GAME.write().unwrap() = Some(game);
-    *BALL.write().unwrap() = ball;
+    set_game(Arena::Dropshot, game, ball);
 }
 
 #[pyfunction]
 fn load_hoops() {
     let (game, ball) = rl_ball_sym::compressed::load_hoops();
-
-    *GAME.write().unwrap() = Some(game);
-    *BALL.write().unwrap() = ball;
+    set_game(Arena::Hoops, game, ball);
 }
 
 #[pyfunction]
 fn load_soccar_throwback() {
     let (game, ball) = rl_ball_sym::compressed::load_soccar_throwback();
-
-    *GAME.write().unwrap() = Some(game);
-    *BALL.write().unwrap() = ball;
+    set_game(Arena::SoccarThrowback, game, ball);
 }
 
 #[pyfunction]
@@ -98,6 +115,7 @@ fn load_soccer_throwback() {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BoostAmount {
     Default,
     Unlimited,
@@ -120,11 +138,19 @@ impl BoostAmount {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mutators {
     boost_amount: BoostAmount,
     boost_accel: f32,
 }
 
+impl Default for Mutators {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Mutators {
     #[inline]
     #[must_use]
@@ -147,17 +173,50 @@ impl Mutators {
             },
         })
     }
+
+    /// How much of the car's boost can be used, which is endless or none with some mutators
+    #[inline]
+    pub fn usable_boost(self, boost: u8) -> f32 {
+        match self.boost_amount {
+            BoostAmount::Unlimited => f32::INFINITY,
+            BoostAmount::NoBoost => 0.,
+            _ => f32::from(boost),
+        }
+    }
 }
 
 #[pyfunction]
 fn set_mutator_settings(py: Python, mutators: PyObject) -> PyResult<()> {
-    *MUTATORS.write().unwrap() = Mutators::from(mutators.as_ref(py))?;
+    let mutators = Mutators::from(mutators.as_ref(py))?;
+    *MUTATORS.write().unwrap() = mutators;
+
+    replay::record(|| Event::Mutators(mutators));
 
     Ok(())
 }
 
 #[pyfunction]
 fn tick(py: Python, packet: PyObject, prediction_time: Option<f32>) -> PyResult<()> {
+    let py_packet = packet.as_ref(py);
+    let packet = py_packet.extract::<GamePacket>()?;
+
+    let py_game_cars = py_packet.getattr("game_cars")?;
+    let game_cars = (0..packet.num_cars).map(|i| py_game_cars.get_item(i)?.extract()).collect::<PyResult<Vec<GameCar>>>()?;
+
+    Ok(update_game(packet, &game_cars, prediction_time.unwrap_or(6.))?)
+}
+
+/// Update everything from the latest game state, which is what `tick` does once the packet has been parsed
+fn update_game(packet: GamePacket, game_cars: &[GameCar], prediction_time: f32) -> Result<(), LibError> {
+    let radius = packet.game_ball.collision_shape.get_radius()?;
+
+    let frame = replay::Frame { packet, cars: game_cars.to_vec() };
+    replay::record(|| Event::Tick {
+        frame: frame.clone(),
+        prediction_time,
+    });
+    *LAST_TICK.write().unwrap() = Some((frame, prediction_time));
+
     TARGETS.write().unwrap().iter_mut().for_each(|target| {
         if matches!(target, Some(t) if !t.is_confirmed()) {
             *target = None;
@@ -165,13 +224,10 @@ fn tick(py: Python, packet: PyObject, prediction_time: Option<f32>) -> PyResult<
     });
 
     let mut game_guard = GAME.write().unwrap();
-    let game = game_guard.as_mut().ok_or_else(|| PyErr::new::<NoGamePyErr, _>(NO_GAME_ERR))?;
+    let game = game_guard.as_mut().ok_or(LibError::NoGame)?;
 
     let mut ball = *BALL.read().unwrap();
 
-    let py_packet = packet.as_ref(py);
-    let packet = py_packet.extract::<GamePacket>()?;
-
     // Get general game information
     *GAME_TIME.write().unwrap() = packet.game_info.seconds_elapsed;
     game.gravity.z = packet.game_info.world_gravity_z;
@@ -185,8 +241,6 @@ fn tick(py: Python, packet: PyObject, prediction_time: Option<f32>) -> PyResult<
         packet.game_ball.physics.angular_velocity.into(),
     );
 
-    let radius = packet.game_ball.collision_shape.get_radius();
-
     // check if the new radius is different
     // if is is, set it
     if (ball.radius() - radius).abs() > 0.1 {
@@ -194,19 +248,18 @@ fn tick(py: Python, packet: PyObject, prediction_time: Option<f32>) -> PyResult<
     }
 
     // Predict future information about the ball
-    *BALL_STRUCT.write().unwrap() = ball.get_ball_prediction_struct_for_time(game, prediction_time.unwrap_or(6.));
+    *BALL_STRUCT.write().unwrap() = ball.get_ball_prediction_struct_for_time(game, prediction_time);
 
     // Get information about the cars on the field
     let mut cars = CARS.write().unwrap();
 
-    if cars.len() != packet.num_cars {
+    if cars.len() != game_cars.len() {
         const NEW_CAR: Car = Car::new();
-        cars.resize(packet.num_cars, NEW_CAR);
+        cars.resize(game_cars.len(), NEW_CAR);
     }
 
-    let py_game_cars = py_packet.getattr("game_cars")?;
-    for (i, car) in cars.iter_mut().enumerate() {
-        car.update(py_game_cars.get_item(i)?.extract()?, packet.game_info.seconds_elapsed);
+    for (car, game_car) in cars.iter_mut().zip(game_cars) {
+        car.update(*game_car, packet.game_info.seconds_elapsed);
     }
 
     Ok(())
@@ -232,24 +285,38 @@ fn get_num_ball_slices() -> usize {
 }
 
 #[pyfunction]
-fn new_target(left_target: [f32; 3], right_target: [f32; 3], car_index: usize, options: Option<TargetOptions>) -> PyResult<usize> {
+fn get_post_correction(ball_slice: BallSlice, left_target: [f32; 3], right_target: [f32; 3]) -> PostCorrectionInfo {
+    let ball_location = ball_slice.get_location();
+    let post_correction = PostCorrection::from(ball_location, BALL.read().unwrap().collision_radius(), left_target.into(), right_target.into());
+
+    PostCorrectionInfo::from(&post_correction, ball_location)
+}
+
+fn add_target(location: Option<TargetLocation>, car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    replay::record(|| Event::NewTarget {
+        location: location.clone(),
+        car_index,
+        options,
+    });
+
     let num_slices = BALL_STRUCT.read().unwrap().len();
 
     if num_slices == 0 {
-        return Err(PyErr::new::<NoSlicesPyErr, _>(NO_SLICES_ERR));
+        return Err(LibError::NoSlices);
     }
 
-    let target_left = left_target.into();
-    let target_right = right_target.into();
     let options = Options::from(options, num_slices);
 
     {
         let mut cars = CARS.write().unwrap();
-        let car = cars.get_mut(car_index).ok_or_else(|| PyErr::new::<NoCarPyErr, _>(NO_CAR_ERR))?;
-        car.init(GRAVITY.read().unwrap().z, num_slices, *MUTATORS.read().unwrap());
+        let car = cars.get_mut(car_index).ok_or(LibError::NoCar)?;
+        car.init(GRAVITY.read().unwrap().z, GAME.read().unwrap().as_ref(), num_slices, *MUTATORS.read().unwrap());
     }
 
-    let target = Some(Target::new(target_left, target_right, car_index, options));
+    let target = Some(match location {
+        Some(location) => Target::new(location, car_index, options),
+        None => Target::new_any(car_index, options),
+    });
     let mut targets = TARGETS.write().unwrap();
 
     let target_position = targets.iter().position(Option::is_none);
@@ -265,55 +332,84 @@ fn new_target(left_target: [f32; 3], right_target: [f32; 3], car_index: usize, o
 }
 
 #[pyfunction]
-fn new_any_target(car_index: usize, options: Option<TargetOptions>) -> PyResult<usize> {
-    let num_slices = BALL_STRUCT.read().unwrap().len();
+fn new_target(left_target: [f32; 3], right_target: [f32; 3], car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    add_target(Some(TargetLocation::new(left_target.into(), right_target.into())), car_index, options)
+}
 
-    if num_slices == 0 {
-        return Err(PyErr::new::<NoSlicesPyErr, _>(NO_SLICES_ERR));
-    }
+#[pyfunction]
+fn new_window_target(left_target: [f32; 3], right_target: [f32; 3], bottom: f32, top: f32, car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    add_target(Some(TargetLocation::window(left_target.into(), right_target.into(), bottom, top)), car_index, options)
+}
 
-    let options = Options::from(options, num_slices);
+#[pyfunction]
+fn new_point_target(point: [f32; 3], tolerance_angle: f32, car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    add_target(
+        Some(TargetLocation::Point {
+            point: point.into(),
+            tolerance_angle,
+        }),
+        car_index,
+        options,
+    )
+}
 
-    {
-        let mut cars = CARS.write().unwrap();
-        let car = cars.get_mut(car_index).ok_or_else(|| PyErr::new::<NoCarPyErr, _>(NO_CAR_ERR))?;
-        car.init(GRAVITY.read().unwrap().z, num_slices, *MUTATORS.read().unwrap());
+#[pyfunction]
+fn new_area_target(polygon: Vec<[f32; 3]>, car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    if polygon.len() < 3 {
+        return Err(LibError::BadArea);
     }
 
-    let target = Some(Target::new_any(car_index, options));
-    let mut targets = TARGETS.write().unwrap();
+    add_target(Some(TargetLocation::Area(polygon.into_iter().map(Vec3A::from).collect())), car_index, options)
+}
 
-    let target_position = targets.iter().position(Option::is_none);
-    let target_index = if let Some(i) = target_position {
-        targets[i] = target;
-        i
-    } else {
-        targets.push(target);
-        targets.len() - 1
+#[pyfunction]
+fn new_away_target(left_target: [f32; 3], right_target: [f32; 3], car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    let location = TargetLocation::AwayFromPosts {
+        left: left_target.into(),
+        right: right_target.into(),
     };
 
-    Ok(target_index)
+    add_target(Some(location), car_index, options)
+}
+
+#[pyfunction]
+fn new_away_from_point_target(point: [f32; 3], tolerance_angle: f32, car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    add_target(
+        Some(TargetLocation::AwayFromPoint {
+            point: point.into(),
+            tolerance_angle,
+        }),
+        car_index,
+        options,
+    )
+}
+
+#[pyfunction]
+fn new_any_target(car_index: usize, options: Option<TargetOptions>) -> Result<usize, LibError> {
+    add_target(None, car_index, options)
 }
 
 #[pyfunction]
-fn confirm_target(target_index: usize) -> PyResult<()> {
+fn confirm_target(target_index: usize) -> Result<(), LibError> {
     let mut targets = TARGETS.write().unwrap();
-    let target = targets
-        .get_mut(target_index)
-        .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?
-        .as_mut()
-        .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?;
+    let target = targets.get_mut(target_index).ok_or(LibError::NoTarget)?.as_mut().ok_or(LibError::NoTarget)?;
 
     if target.shot.is_none() {
-        return Err(PyErr::new::<NoShotPyErr, _>(NO_SHOT_ERR));
+        return Err(LibError::NoShot);
     }
 
     target.confirm();
+
+    // only confirmations that worked are recorded, so they can be replayed without the shot
+    replay::record(|| Event::ConfirmTarget(target_index));
+
     Ok(())
 }
 
 #[pyfunction]
 fn remove_target(target_index: usize) {
+    replay::record(|| Event::RemoveTarget(target_index));
+
     let mut targets = TARGETS.write().unwrap();
     if targets.get(target_index).is_none() {
         return;
@@ -354,7 +450,32 @@ fn get_shot_with_target(
     may_double_jump_shot: Option<bool>,
     may_aerial_shot: Option<bool>,
     only: Option<bool>,
-) -> PyResult<BasicShotInfo> {
+) -> Result<BasicShotInfo, LibError> {
+    let result = find_shot_with_target(target_index, temporary, may_ground_shot, may_jump_shot, may_double_jump_shot, may_aerial_shot, only);
+
+    replay::record(|| Event::ShotQuery {
+        target_index,
+        temporary,
+        may_ground_shot,
+        may_jump_shot,
+        may_double_jump_shot,
+        may_aerial_shot,
+        only,
+        result: result.as_ref().ok().and_then(BasicShotInfo::get_time),
+    });
+
+    result
+}
+
+fn find_shot_with_target(
+    target_index: usize,
+    temporary: Option<bool>,
+    may_ground_shot: Option<bool>,
+    may_jump_shot: Option<bool>,
+    may_double_jump_shot: Option<bool>,
+    may_aerial_shot: Option<bool>,
+    only: Option<bool>,
+) -> Result<BasicShotInfo, LibError> {
     let only = only.unwrap_or(false);
     let may_ground_shot = may_ground_shot.unwrap_or(!only);
     let may_jump_shot = may_jump_shot.unwrap_or(!only);
@@ -363,7 +484,7 @@ fn get_shot_with_target(
     let temporary = temporary.unwrap_or(false);
 
     if !may_ground_shot && !may_jump_shot && !may_double_jump_shot && !may_aerial_shot {
-        return Err(PyErr::new::<NoShotSelectedPyErr, _>(NO_SHOT_SELECTED_ERR));
+        return Err(LibError::NoShotSelected);
     }
 
     let mutators = *MUTATORS.read().unwrap();
@@ -373,17 +494,15 @@ fn get_shot_with_target(
 
     let mut found_shot = None;
     let mut basic_shot_info = None;
+    let mut rejections = Vec::new();
 
     {
         let targets_gaurd = TARGETS.read().unwrap();
-        let target = targets_gaurd
-            .get(target_index)
-            .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?
-            .as_ref()
-            .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?;
+        let target = targets_gaurd.get(target_index).ok_or(LibError::NoTarget)?.as_ref().ok_or(LibError::NoTarget)?;
 
-        let cars = CARS.read().unwrap();
-        let car = cars.get(target.car_index).ok_or_else(|| PyErr::new::<NoCarPyErr, _>(NO_CAR_ERR))?;
+        let mut cars = CARS.write().unwrap();
+        let car = cars.get_mut(target.car_index).ok_or(LibError::NoCar)?;
+        car.init(gravity.z, GAME.read().unwrap().as_ref(), ball_prediction.len(), mutators);
 
         if ball_prediction.is_empty() || car.demolished || car.landing_time >= ball_prediction.last().map(|slice| slice.time).unwrap_or_default() {
             return Ok(BasicShotInfo::not_found());
@@ -409,116 +528,32 @@ fn get_shot_with_target(
 
         for (i, ball) in ball_prediction[target.options.min_slice..target.options.max_slice].iter().enumerate() {
             if ball.location.y.abs() > 5120. + ball.collision_radius() {
+                if target.options.debug {
+                    rejections.push((ball.time, ShotRejection::OutOfField));
+                }
+
                 break;
             }
 
             let max_time_remaining = ball.time - game_time;
 
-            let shot_type = match analyzer.get_shot_type(ball.location, max_time_remaining) {
-                Ok(st) => st,
-                Err(_) => continue,
-            };
-
-            if let Some(target_location) = &target.location {
-                let post_info = PostCorrection::from(ball.location, ball.collision_radius(), target_location.left, target_location.right);
-
-                if !post_info.fits {
-                    continue;
-                }
-
-                let shot_vector = post_info.get_shot_vector_target(car.landing_location, ball.location);
-
-                if shot_type == ShotType::Aerial {
-                    let ball_edge = ball.location - flatten(shot_vector) * ball.radius();
-                    let target_location = ball_edge - Vec3A::new(0., 0., shot_vector.z) * (car.hitbox_offset.x + car.hitbox.length) / 2.;
-
-                    let target_info = match analyzer.aerial_shot(mutators, target_location, shot_vector, max_time_remaining, Some(ball.location)) {
-                        Ok(ti) => ti,
-                        Err(_) => continue,
-                    };
-
-                    if found_shot.is_none() {
-                        basic_shot_info = Some(target_info.get_basic_shot_info(ball.time));
-
-                        found_shot = Some(if temporary { AirBasedShot::default() } else { AirBasedShot::from(ball, &target_info) }.into());
-
-                        if !target.options.all {
-                            break;
-                        }
-                    }
-
-                    continue;
-                }
-
-                let target_info = match analyzer.target(ball, shot_vector, max_time_remaining, i, shot_type) {
-                    Ok(ti) => ti,
-                    Err(_) => continue,
-                };
-
-                if target_info.can_reach(car, max_time_remaining, mutators).is_err() {
-                    continue;
-                }
-
-                if found_shot.is_none() {
-                    basic_shot_info = Some(target_info.get_basic_shot_info(ball.time));
-
-                    found_shot = Some(
-                        if temporary {
-                            GroundBasedShot::default()
-                        } else {
-                            GroundBasedShot::from(ball, &target_info)
-                        }
-                        .into(),
-                    );
-
-                    if !target.options.all {
-                        break;
-                    }
-                }
-            } else if shot_type == ShotType::Aerial {
-                let ball_edge = ball.location - flatten(ball.location - car.location).normalize_or_zero() * ball.radius();
-                let shot_vector = (ball_edge - car.location).normalize_or_zero();
-                let target_location = ball_edge - shot_vector * (car.hitbox_offset.x + car.hitbox.length) / 2.;
-
-                let target_info = match analyzer.aerial_shot(mutators, target_location, shot_vector, max_time_remaining, None) {
-                    Ok(ti) => ti,
-                    Err(_) => continue,
-                };
-
-                if found_shot.is_none() {
-                    basic_shot_info = Some(target_info.get_basic_shot_info(ball.time));
-
-                    found_shot = Some(if temporary { AirBasedShot::default() } else { AirBasedShot::from(ball, &target_info) }.into());
-
-                    if !target.options.all {
-                        break;
+            let slice_shot = match analyzer.check_slice(target, ball, i, max_time_remaining, mutators) {
+                Ok(slice_shot) => slice_shot,
+                Err(rejection) => {
+                    if target.options.debug {
+                        rejections.push((ball.time, rejection));
                     }
-                }
-            } else {
-                let target_info = match analyzer.no_target(ball, max_time_remaining, i, shot_type) {
-                    Ok(ti) => ti,
-                    Err(_) => continue,
-                };
 
-                if target_info.can_reach(car, max_time_remaining, mutators).is_err() {
                     continue;
                 }
+            };
 
-                if found_shot.is_none() {
-                    basic_shot_info = Some(target_info.get_basic_shot_info(ball.time));
-
-                    found_shot = Some(
-                        if temporary {
-                            GroundBasedShot::default()
-                        } else {
-                            GroundBasedShot::from(ball, &target_info)
-                        }
-                        .into(),
-                    );
+            if found_shot.is_none() {
+                basic_shot_info = Some(slice_shot.get_basic_shot_info(ball.time));
+                found_shot = Some(slice_shot.into_shot(ball, temporary));
 
-                    if !target.options.all {
-                        break;
-                    }
+                if !target.options.all {
+                    break;
                 }
             }
         }
@@ -529,74 +564,275 @@ fn get_shot_with_target(
             .write()
             .unwrap()
             .get_mut(target_index)
-            .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?
+            .ok_or(LibError::NoTarget)?
             .as_mut()
-            .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?
+            .ok_or(LibError::NoTarget)?
             .shot = found_shot;
     }
 
-    Ok(basic_shot_info.unwrap_or_default())
+    Ok(basic_shot_info.unwrap_or_default().with_rejections(rejections))
 }
 
 #[pyfunction]
-fn get_data_for_shot_with_target(target_index: usize) -> PyResult<AdvancedShotInfo> {
+fn get_data_for_shot_with_target(target_index: usize) -> Result<AdvancedShotInfo, LibError> {
     let targets_gaurd = TARGETS.read().unwrap();
-    let target = targets_gaurd
-        .get(target_index)
-        .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?
-        .as_ref()
-        .ok_or_else(|| PyErr::new::<NoTargetPyErr, _>(NO_TARGET_ERR))?;
-    let shot = target.shot.as_ref().ok_or_else(|| PyErr::new::<NoShotPyErr, _>(NO_SHOT_ERR))?;
+    let target = targets_gaurd.get(target_index).ok_or(LibError::NoTarget)?.as_ref().ok_or(LibError::NoTarget)?;
+    let shot = target.shot.as_ref().ok_or(LibError::NoShot)?;
 
     let time_remaining = shot.time() - *GAME_TIME.read().unwrap();
 
     if time_remaining < 0. {
-        return Err(PyErr::new::<NoTimeRemainingPyErr, _>(NO_TIME_REMAINING_ERR));
+        return Err(LibError::NoTimeRemaining);
     }
 
-    let cars_guard = CARS.read().unwrap();
-    let car = cars_guard.get(target.car_index).ok_or_else(|| PyErr::new::<NoCarPyErr, _>(NO_CAR_ERR))?;
-
     let ball_struct = BALL_STRUCT.read().unwrap();
+
+    let mut cars_guard = CARS.write().unwrap();
+    let car = cars_guard.get_mut(target.car_index).ok_or(LibError::NoCar)?;
+    car.init(GRAVITY.read().unwrap().z, GAME.read().unwrap().as_ref(), ball_struct.len(), *MUTATORS.read().unwrap());
+
     let slice_num = ((time_remaining * TPS).round() as usize).clamp(1, ball_struct.len()) - 1;
     let ball = ball_struct[slice_num];
 
     if ball.location.distance(shot.ball_location()) > car.hitbox.width {
-        return Err(PyErr::new::<BallChangedPyErr, _>(BALL_CHANGED_ERR));
+        return Err(LibError::BallChanged);
     }
 
     match shot {
         Shot::GroundBased(shot_details) => {
-            let shot_info = AdvancedShotInfo::get_from_ground(car, shot_details).ok_or_else(|| PyErr::new::<StrayedFromPathPyErr, _>(STRAYED_FROM_PATH_ERR))?;
+            let shot_info = AdvancedShotInfo::get_from_ground(car, shot_details).ok_or(LibError::StrayedFromPath)?;
 
             if car.max_speed[slice_num] * (time_remaining + 0.1) >= shot_info.get_distance_remaining() {
                 Ok(shot_info)
             } else {
-                Err(PyErr::new::<BadAccelerationPyErr, _>(BAD_ACCELERATION_ERR))
+                Err(LibError::BadAcceleration)
             }
         }
         Shot::AirBased(shot_details) => {
             let shot_info = AdvancedShotInfo::get_from_air(car, shot_details);
 
             let gravity = *GRAVITY.read().unwrap();
-            let vf_base = car.velocity + gravity * time_remaining;
-            let xf_base = car.velocity * time_remaining + gravity * 0.5 * time_remaining.powi(2);
-
-            let mutators = MUTATORS.read().unwrap();
-
-            if air::partial_validate(
-                shot_details.final_target,
-                car.location + xf_base,
-                vf_base,
-                mutators.boost_amount,
-                mutators.boost_accel,
-                f32::from(car.boost),
-                shot_details.time - *GAME_TIME.read().unwrap(),
-            ) {
+            let mutators = *MUTATORS.read().unwrap();
+
+            if air::is_still_valid(car, shot_details, gravity, mutators, shot_details.time - *GAME_TIME.read().unwrap()) {
                 Ok(shot_info)
             } else {
-                Err(PyErr::new::<BadAccelerationPyErr, _>(BAD_ACCELERATION_ERR))
+                Err(LibError::BadAcceleration)
             }
         }
     }
 }
+
+#[pyfunction]
+fn get_controls_for_shot(target_index: usize) -> Result<ShotControls, LibError> {
+    let targets_gaurd = TARGETS.read().unwrap();
+    let target = targets_gaurd.get(target_index).ok_or(LibError::NoTarget)?.as_ref().ok_or(LibError::NoTarget)?;
+    let shot = target.shot.as_ref().ok_or(LibError::NoShot)?;
+
+    let time_remaining = shot.time() - *GAME_TIME.read().unwrap();
+
+    if time_remaining < 0. {
+        return Err(LibError::NoTimeRemaining);
+    }
+
+    let num_slices = BALL_STRUCT.read().unwrap().len();
+    let mutators = *MUTATORS.read().unwrap();
+
+    let mut cars_guard = CARS.write().unwrap();
+    let car = cars_guard.get_mut(target.car_index).ok_or(LibError::NoCar)?;
+    car.init(GRAVITY.read().unwrap().z, GAME.read().unwrap().as_ref(), num_slices, mutators);
+
+    match shot {
+        Shot::GroundBased(shot_details) => {
+            let shot_info = AdvancedShotInfo::get_from_ground(car, shot_details).ok_or(LibError::StrayedFromPath)?;
+
+            Ok(ShotControls::get_from_ground(car, shot_details, &shot_info, mutators, time_remaining))
+        }
+        Shot::AirBased(shot_details) => Ok(ShotControls::get_from_air(car, shot_details, *GRAVITY.read().unwrap(), mutators, time_remaining)),
+    }
+}
+
+#[pyfunction]
+fn get_follow_up_for_shot(target_index: usize, max_time: Option<f32>, flip_reset: Option<bool>) -> Result<FollowUpInfo, LibError> {
+    let targets_gaurd = TARGETS.read().unwrap();
+    let target = targets_gaurd.get(target_index).ok_or(LibError::NoTarget)?.as_ref().ok_or(LibError::NoTarget)?;
+    let shot = match target.shot.as_ref().ok_or(LibError::NoShot)? {
+        Shot::AirBased(shot_details) => shot_details,
+        Shot::GroundBased(_) => return Err(LibError::NotAirShot),
+    };
+
+    let time_remaining = shot.time - *GAME_TIME.read().unwrap();
+
+    if time_remaining < 0. {
+        return Err(LibError::NoTimeRemaining);
+    }
+
+    let game_guard = GAME.read().unwrap();
+    let game = game_guard.as_ref().ok_or(LibError::NoGame)?;
+
+    // the shot has to be within the ball prediction
+    let ball_struct = BALL_STRUCT.read().unwrap();
+    let slice_num = ((time_remaining * TPS).round() as usize).max(1);
+    let ball = *ball_struct.get(slice_num - 1).ok_or(LibError::BadTime)?;
+
+    let gravity = *GRAVITY.read().unwrap();
+    let mutators = *MUTATORS.read().unwrap();
+
+    let mut cars_guard = CARS.write().unwrap();
+    let car = cars_guard.get_mut(target.car_index).ok_or(LibError::NoCar)?;
+    car.init(gravity.z, Some(game), ball_struct.len(), mutators);
+
+    let (next_car, next_ball) = follow_up::predict_touch(car, shot, &ball, mutators, game, time_remaining, flip_reset.unwrap_or(false));
+    let ball_prediction = next_ball.get_ball_prediction_struct_for_time(game, max_time.unwrap_or(DEFAULT_FOLLOW_UP_TIME));
+
+    Ok(match follow_up::find_follow_up(&next_car, &ball_prediction, mutators, gravity) {
+        Ok((ball, target_info)) => FollowUpInfo::found(ball.time, target_info.final_target, ball.location),
+        Err(LibError::NoFollowUp) => FollowUpInfo::not_found(),
+        Err(error) => return Err(error),
+    })
+}
+
+#[pyfunction]
+fn get_render_for_shot(target_index: usize) -> Result<ShotRender, LibError> {
+    let targets_gaurd = TARGETS.read().unwrap();
+    let target = targets_gaurd.get(target_index).ok_or(LibError::NoTarget)?.as_ref().ok_or(LibError::NoTarget)?;
+    let shot = target.shot.as_ref().ok_or(LibError::NoShot)?;
+
+    let num_slices = BALL_STRUCT.read().unwrap().len();
+
+    let mut cars_guard = CARS.write().unwrap();
+    let car = cars_guard.get_mut(target.car_index).ok_or(LibError::NoCar)?;
+    car.init(GRAVITY.read().unwrap().z, GAME.read().unwrap().as_ref(), num_slices, *MUTATORS.read().unwrap());
+
+    Ok(render::shot_render(car, shot, target.location.as_ref(), &BALL.read().unwrap()))
+}
+
+#[pyfunction]
+fn is_dribbling(car_index: usize) -> Result<bool, LibError> {
+    let cars = CARS.read().unwrap();
+    let car = cars.get(car_index).ok_or(LibError::NoCar)?;
+
+    Ok(dribble::DribbleState::from(car, &BALL.read().unwrap()).is_some())
+}
+
+#[pyfunction]
+fn get_dribble_controls(target_index: usize, defender_index: Option<usize>) -> Result<DribbleControls, LibError> {
+    let targets_gaurd = TARGETS.read().unwrap();
+    let target = targets_gaurd.get(target_index).ok_or(LibError::NoTarget)?.as_ref().ok_or(LibError::NoTarget)?;
+
+    let ball = *BALL.read().unwrap();
+    let cars = CARS.read().unwrap();
+    let car = cars.get(target.car_index).ok_or(LibError::NoCar)?;
+    let defender = defender_index.map(|index| cars.get(index).ok_or(LibError::NoCar)).transpose()?;
+
+    let state = dribble::DribbleState::from(car, &ball).ok_or(LibError::NotDribbling)?;
+    let post_info = target.location.as_ref().map(|location| location.post_correction(ball.location, ball.collision_radius()));
+
+    Ok(DribbleControls::from(dribble::dribble_controls(
+        car,
+        &ball,
+        state,
+        post_info,
+        defender,
+        *MUTATORS.read().unwrap(),
+        GRAVITY.read().unwrap().z,
+    )))
+}
+
+#[pyfunction]
+fn get_shadow_position(car_index: usize, opponent_index: usize, goal: [f32; 3], distance: f32) -> Result<ShadowInfo, LibError> {
+    if car_index == opponent_index {
+        return Err(LibError::SameCar);
+    }
+
+    let ball_struct = BALL_STRUCT.read().unwrap();
+    let gravity = GRAVITY.read().unwrap().z;
+    let mutators = *MUTATORS.read().unwrap();
+
+    let mut cars = CARS.write().unwrap();
+
+    for index in [car_index, opponent_index] {
+        let car = cars.get_mut(index).ok_or(LibError::NoCar)?;
+        car.init(gravity, GAME.read().unwrap().as_ref(), ball_struct.len(), mutators);
+    }
+
+    let plan = shadow::shadow_position(&cars[car_index], &cars[opponent_index], &ball_struct, *GAME_TIME.read().unwrap(), goal.into(), distance).ok_or(LibError::NoSlices)?;
+
+    Ok(ShadowInfo::from(plan))
+}
+
+#[pyfunction]
+fn get_kickoff_plan(car_index: usize, opponent_index: Option<usize>) -> Result<KickoffInfo, LibError> {
+    // the spawns are only known for the standard soccar arena
+    if *ARENA.read().unwrap() != Some(Arena::Soccar) {
+        return Err(LibError::NotSoccar);
+    }
+
+    let num_slices = BALL_STRUCT.read().unwrap().len();
+    if num_slices == 0 {
+        return Err(LibError::NoSlices);
+    }
+
+    let gravity = GRAVITY.read().unwrap().z;
+    let mutators = *MUTATORS.read().unwrap();
+
+    let mut cars = CARS.write().unwrap();
+
+    for index in [Some(car_index), opponent_index].into_iter().flatten() {
+        let car = cars.get_mut(index).ok_or(LibError::NoCar)?;
+        car.init(gravity, GAME.read().unwrap().as_ref(), num_slices, mutators);
+    }
+
+    let ball = *BALL.read().unwrap();
+    let opponent = opponent_index.map(|index| &cars[index]);
+    let plan = kickoff::plan_kickoff(&cars[car_index], opponent, ball.location, ball.radius()).ok_or(LibError::NotKickoff)?;
+
+    Ok(KickoffInfo::from(plan))
+}
+
+#[pyfunction]
+fn new_car_sim(car_index: usize) -> Result<PyCarSim, LibError> {
+    let num_slices = BALL_STRUCT.read().unwrap().len();
+    let gravity = *GRAVITY.read().unwrap();
+    let mutators = *MUTATORS.read().unwrap();
+
+    let mut cars_guard = CARS.write().unwrap();
+    let car = cars_guard.get_mut(car_index).ok_or(LibError::NoCar)?;
+    car.init(gravity.z, GAME.read().unwrap().as_ref(), num_slices, mutators);
+
+    Ok(PyCarSim::from(car_sim::CarSim::from(car, gravity, mutators, *GAME_TIME.read().unwrap())))
+}
+
+#[pyfunction]
+fn start_recording() {
+    replay::start_recording();
+}
+
+#[pyfunction]
+fn stop_recording(path: String) -> Result<(), LibError> {
+    let recording = RECORDING.write().unwrap().take().ok_or(LibError::NotRecording)?;
+
+    fs::write(path, recording).map_err(|_| LibError::ReplayFile)
+}
+
+#[pyfunction]
+fn replay_recording(path: String) -> Result<Vec<ReplayedShot>, LibError> {
+    let bytes = fs::read(path).map_err(|_| LibError::ReplayFile)?;
+    let events = replay::read_replay(&bytes).ok_or(LibError::BadReplay)?;
+
+    Ok(replay::play(&events).into_iter().map(ReplayedShot::from).collect())
+}
+
+#[cfg(feature = "serde")]
+#[pyfunction]
+fn get_state_json() -> Result<String, LibError> {
+    serde_json::to_string(&export::AnalyzerState::get()).map_err(|_| LibError::Serialize)
+}
+
+#[cfg(feature = "serde")]
+#[pyfunction]
+fn get_state_bincode(py: Python) -> Result<PyObject, LibError> {
+    let bytes = bincode::serialize(&export::AnalyzerState::get()).map_err(|_| LibError::Serialize)?;
+
+    Ok(pyo3::types::PyBytes::new(py, &bytes).into())
+}
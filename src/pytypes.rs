@@ -1,14 +1,25 @@
 use crate::{
+    air::{self, aerial_controls, should_jump, AerialControls},
+    air_sim::AirState,
     car::Car,
+    car_sim,
     constants::*,
+    dribble::DribblePlan,
+    ground::{pure_pursuit_steer, speed_controller, turn_speed_limit},
+    kickoff::KickoffPlan,
+    replay::ReplayedQuery,
+    shadow::ShadowPlan,
     shot::{AirBasedShot, GroundBasedShot},
-    utils::{flatten, get_tuple_from_vec3},
+    utils::{flatten, get_tuple_from_vec3, PostCorrection},
+    Mutators,
 };
+use dubins_paths::PathType;
 use glam::Vec3A;
 use pyo3::{pyclass, pymethods, FromPyObject};
 use rl_ball_sym::simulation::ball::Ball;
 
 #[derive(Clone, Copy, Debug, Default, FromPyObject)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hitbox {
     pub length: f32,
     pub width: f32,
@@ -33,6 +44,13 @@ pub struct GameVec {
     pub z: f32,
 }
 
+impl From<Vec3A> for GameVec {
+    #[inline]
+    fn from(vec: Vec3A) -> Self {
+        Self { x: vec.x, y: vec.y, z: vec.z }
+    }
+}
+
 impl From<GameVec> for Vec3A {
     #[inline]
     fn from(gv: GameVec) -> Self {
@@ -69,6 +87,7 @@ pub struct GameBox {
 #[derive(Clone, Copy, Debug, Default, FromPyObject)]
 pub struct GameCylinder {
     pub diameter: f32,
+    #[allow(dead_code)]
     pub height: f32,
 }
 
@@ -84,12 +103,26 @@ pub struct GameCollisionShape {
 
 impl GameCollisionShape {
     #[inline]
-    pub fn get_radius(&self) -> f32 {
+    pub const fn from_radius(radius: f32) -> Self {
+        Self {
+            shape_type: 1,
+            box_: GameBox {
+                length: 0.,
+                width: 0.,
+                height: 0.,
+            },
+            sphere: GameSphere { diameter: radius * 2. },
+            cylinder: GameCylinder { diameter: 0., height: 0. },
+        }
+    }
+
+    #[inline]
+    pub fn get_radius(&self) -> Result<f32, LibError> {
         match self.shape_type {
-            0 => (self.box_.length + self.box_.width + self.box_.height) / 6.,
-            1 => self.sphere.diameter / 2.,
-            2 => self.cylinder.diameter / 2.,
-            _ => panic!("Invalid shape type: #{}", self.shape_type),
+            0 => Ok((self.box_.length + self.box_.width + self.box_.height) / 6.),
+            1 => Ok(self.sphere.diameter / 2.),
+            2 => Ok(self.cylinder.diameter / 2.),
+            _ => Err(LibError::UnknownShape),
         }
     }
 }
@@ -133,8 +166,21 @@ pub struct GamePacket {
     pub num_cars: usize,
 }
 
+#[derive(Clone, Copy, Debug, Default, FromPyObject)]
+pub struct ControllerState {
+    pub throttle: f32,
+    pub steer: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
+    pub jump: bool,
+    pub boost: bool,
+    pub handbrake: bool,
+}
+
 #[pyclass(frozen)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ShotType {
     Ground = 0,
     Jump,
@@ -154,30 +200,88 @@ impl ShotType {
     }
 }
 
+/// Why a ball slice couldn't be used for a shot
+#[pyclass(frozen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShotRejection {
+    /// the shot type that's needed for the ball's height is disabled
+    ShotTypeDisabled = 0,
+    /// the car won't have been on the ground for long enough to aerial
+    TooSoonAfterLanding,
+    /// the ball is in a goal, or the path would leave the field
+    OutOfField,
+    /// the ball won't fit between the target's posts
+    PostCorrection,
+    /// the path is longer than the car can drive before the ball gets there
+    PathTooLong,
+    /// there isn't enough time to jump to the ball
+    NotEnoughJumpTime,
+    /// driving the path tick-by-tick gets to the ball too late
+    TooSlow,
+    /// the car can't get to the aerial's target in time, even at max speed
+    AerialTooFar,
+    /// no jump type can get the car to the aerial's target with its boost and turning speed
+    AerialUnreachable,
+    /// the aerial failed when simulated tick-by-tick
+    AerialSimulation,
+}
+
+#[pyclass(frozen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KickoffSpawn {
+    Diagonal = 0,
+    OffCenter,
+    Center,
+}
+
+#[pyclass(frozen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KickoffOutcome {
+    Win = 0,
+    FiftyFifty,
+    Lose,
+}
+
 #[pyclass(frozen)]
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TargetOptions {
     pub min_slice: Option<usize>,
     pub max_slice: Option<usize>,
     pub use_absolute_max_values: Option<bool>,
     pub all: Option<bool>,
+    pub simulate_aerials: Option<bool>,
+    /// record why each ball slice was rejected in `BasicShotInfo.rejections`, which is empty without it
+    pub debug: Option<bool>,
 }
 
 #[pymethods]
 impl TargetOptions {
     #[new]
     #[inline]
-    const fn __new__(min_slice: Option<usize>, max_slice: Option<usize>, use_absolute_max_values: Option<bool>, all: Option<bool>) -> Self {
+    const fn __new__(
+        min_slice: Option<usize>,
+        max_slice: Option<usize>,
+        use_absolute_max_values: Option<bool>,
+        all: Option<bool>,
+        simulate_aerials: Option<bool>,
+        debug: Option<bool>,
+    ) -> Self {
         Self {
             min_slice,
             max_slice,
             use_absolute_max_values,
             all,
+            simulate_aerials,
+            debug,
         }
     }
 
     fn __str__(&self) -> String {
-        let mut s = Vec::with_capacity(4);
+        let mut s = Vec::with_capacity(6);
 
         if let Some(min_slice) = self.min_slice {
             s.push(format!("min_slice=={min_slice}"));
@@ -195,20 +299,29 @@ impl TargetOptions {
             s.push(format!("all=={all}"));
         }
 
+        if let Some(simulate_aerials) = self.simulate_aerials {
+            s.push(format!("simulate_aerials=={simulate_aerials}"));
+        }
+
+        if let Some(debug) = self.debug {
+            s.push(format!("debug=={debug}"));
+        }
+
         s.join(", ")
     }
 
     #[inline]
     fn __repr__(&self) -> String {
         format!(
-            "TargetOptions(min_slice={:?}, max_slice={:?}, use_absolute_max_values={:?}, all={:?})",
-            self.min_slice, self.max_slice, self.use_absolute_max_values, self.all
+            "TargetOptions(min_slice={:?}, max_slice={:?}, use_absolute_max_values={:?}, all={:?}, simulate_aerials={:?}, debug={:?})",
+            self.min_slice, self.max_slice, self.use_absolute_max_values, self.all, self.simulate_aerials, self.debug
         )
     }
 }
 
 #[pyclass(frozen)]
 #[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasicShotInfo {
     #[pyo3(get)]
     found: bool,
@@ -220,6 +333,9 @@ pub struct BasicShotInfo {
     shot_vector: (f32, f32, f32),
     #[pyo3(get)]
     is_forwards: bool,
+    /// the time of each ball slice that was checked and why it was rejected, which is only filled in if the target is in debug mode
+    #[pyo3(get)]
+    rejections: Vec<(f32, ShotRejection)>,
 }
 
 impl Default for BasicShotInfo {
@@ -238,6 +354,28 @@ impl BasicShotInfo {
             shot_type: None,
             shot_vector: (0., 0., 0.),
             is_forwards: true,
+            rejections: Vec::new(),
+        }
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub fn get_rejections(&self) -> &[(f32, ShotRejection)] {
+        &self.rejections
+    }
+
+    #[inline]
+    pub fn with_rejections(self, rejections: Vec<(f32, ShotRejection)>) -> Self {
+        Self { rejections, ..self }
+    }
+
+    /// The time of the shot, if one was found
+    #[inline]
+    pub const fn get_time(&self) -> Option<f32> {
+        if self.found {
+            Some(self.time)
+        } else {
+            None
         }
     }
 
@@ -249,6 +387,7 @@ impl BasicShotInfo {
             shot_type: Some(shot_type),
             shot_vector: get_tuple_from_vec3(shot_vector),
             is_forwards,
+            rejections: Vec::new(),
         }
     }
 }
@@ -279,6 +418,119 @@ impl BasicShotInfo {
 
 #[pyclass(frozen)]
 #[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FollowUpInfo {
+    #[pyo3(get)]
+    found: bool,
+    #[pyo3(get)]
+    time: f32,
+    #[pyo3(get)]
+    final_target: (f32, f32, f32),
+    #[pyo3(get)]
+    ball_location: (f32, f32, f32),
+}
+
+impl Default for FollowUpInfo {
+    #[inline]
+    fn default() -> Self {
+        Self::not_found()
+    }
+}
+
+impl FollowUpInfo {
+    #[inline]
+    pub const fn not_found() -> Self {
+        FollowUpInfo {
+            found: false,
+            time: -1.,
+            final_target: (0., 0., 0.),
+            ball_location: (0., 0., 0.),
+        }
+    }
+
+    #[inline]
+    pub const fn found(time: f32, final_target: Vec3A, ball_location: Vec3A) -> Self {
+        FollowUpInfo {
+            found: true,
+            time,
+            final_target: get_tuple_from_vec3(final_target),
+            ball_location: get_tuple_from_vec3(ball_location),
+        }
+    }
+}
+
+#[pymethods]
+impl FollowUpInfo {
+    #[inline]
+    fn __str__(&self) -> String {
+        if self.found {
+            format!("Follow-up touch found at time: {:.2}", self.time)
+        } else {
+            String::from("Not found")
+        }
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        if self.found {
+            format!(
+                "FollowUpInfo(found=True, time={}, final_target={:?}, ball_location={:?})",
+                self.time, self.final_target, self.ball_location
+            )
+        } else {
+            String::from("FollowUpInfo(found=False)")
+        }
+    }
+}
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayedShot {
+    #[pyo3(get)]
+    game_time: f32,
+    #[pyo3(get)]
+    target_index: usize,
+    #[pyo3(get)]
+    recorded_time: Option<f32>,
+    #[pyo3(get)]
+    replayed_time: Option<f32>,
+}
+
+impl From<ReplayedQuery> for ReplayedShot {
+    #[inline]
+    fn from(query: ReplayedQuery) -> Self {
+        ReplayedShot {
+            game_time: query.game_time,
+            target_index: query.target_index,
+            recorded_time: query.recorded,
+            replayed_time: query.replayed,
+        }
+    }
+}
+
+#[pymethods]
+impl ReplayedShot {
+    /// If the replay found the same shot as the recording
+    #[getter]
+    #[inline]
+    fn matches(&self) -> bool {
+        self.recorded_time == self.replayed_time
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "ReplayedShot(game_time={}, target_index={}, recorded_time={:?}, replayed_time={:?})",
+            self.game_time, self.target_index, self.recorded_time, self.replayed_time
+        )
+    }
+}
+
+#[pyclass(frozen)]
+#[derive(Clone)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BallSlice {
     #[pyo3(get)]
     time: f32,
@@ -319,12 +571,71 @@ impl BallSlice {
             angular_velocity: get_tuple_from_vec3(ball.angular_velocity),
         }
     }
+
+    #[inline]
+    pub fn get_location(&self) -> Vec3A {
+        let (x, y, z) = self.location;
+        Vec3A::new(x, y, z)
+    }
+}
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PostCorrectionInfo {
+    #[pyo3(get)]
+    target_left: PyVec3A,
+    #[pyo3(get)]
+    target_right: PyVec3A,
+    #[pyo3(get)]
+    fits: bool,
+    #[pyo3(get)]
+    left_vector: PyVec3A,
+    #[pyo3(get)]
+    right_vector: PyVec3A,
+    #[pyo3(get)]
+    cone_angle: f32,
+}
+
+impl PostCorrectionInfo {
+    pub fn from(post_correction: &PostCorrection, ball_location: Vec3A) -> Self {
+        let (left_vector, right_vector) = post_correction.get_shot_cone(ball_location);
+
+        Self {
+            target_left: get_tuple_from_vec3(post_correction.target_left),
+            target_right: get_tuple_from_vec3(post_correction.target_right),
+            fits: post_correction.fits,
+            left_vector: get_tuple_from_vec3(left_vector),
+            right_vector: get_tuple_from_vec3(right_vector),
+            cone_angle: flatten(left_vector).angle_between(flatten(right_vector)),
+        }
+    }
+}
+
+#[pymethods]
+impl PostCorrectionInfo {
+    #[inline]
+    fn __str__(&self) -> String {
+        format!(
+            "Left post: {:?}, right post: {:?}, fits: {}, cone angle: {:.2}",
+            self.target_left, self.target_right, self.fits, self.cone_angle
+        )
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "PostCorrectionInfo(target_left={:?}, target_right={:?}, fits={}, left_vector={:?}, right_vector={:?}, cone_angle={})",
+            self.target_left, self.target_right, self.fits, self.left_vector, self.right_vector, self.cone_angle
+        )
+    }
 }
 
 type PyVec3A = (f32, f32, f32);
 
 #[pyclass(frozen)]
 #[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdvancedShotInfo {
     #[pyo3(get)]
     final_target: PyVec3A,
@@ -352,6 +663,11 @@ impl AdvancedShotInfo {
     pub const fn get_final_target(&self) -> PyVec3A {
         self.final_target
     }
+
+    #[inline]
+    pub const fn get_current_path_point(&self) -> PyVec3A {
+        self.current_path_point
+    }
 }
 
 #[pymethods]
@@ -404,7 +720,7 @@ impl AdvancedShotInfo {
 
             shot.path_endpoint.pos + additional_space
         } else {
-            shot.path.sample(distance_along + distance).pos
+            shot.sample(distance_along + distance).pos
         };
 
         // get all the samples from the vec after index
@@ -438,3 +754,493 @@ impl AdvancedShotInfo {
         }
     }
 }
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DribbleControls {
+    #[pyo3(get)]
+    ball_offset: PyVec3A,
+    #[pyo3(get)]
+    throttle: f32,
+    #[pyo3(get)]
+    steer: f32,
+    #[pyo3(get)]
+    boost: bool,
+    #[pyo3(get)]
+    flick: bool,
+    #[pyo3(get)]
+    fall_time: Option<f32>,
+}
+
+impl From<DribblePlan> for DribbleControls {
+    #[inline]
+    fn from(plan: DribblePlan) -> Self {
+        Self {
+            ball_offset: get_tuple_from_vec3(plan.ball_offset),
+            throttle: plan.throttle,
+            steer: plan.steer,
+            boost: plan.boost,
+            flick: plan.flick,
+            fall_time: plan.fall_time,
+        }
+    }
+}
+
+#[pymethods]
+impl DribbleControls {
+    #[inline]
+    fn __str__(&self) -> String {
+        format!(
+            "Throttle: {:.2}, steer: {:.2}, boost: {}, flick: {}, fall time: {:?}",
+            self.throttle, self.steer, self.boost, self.flick, self.fall_time
+        )
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "DribbleControls(ball_offset={:?}, throttle={}, steer={}, boost={}, flick={}, fall_time={:?})",
+            self.ball_offset, self.throttle, self.steer, self.boost, self.flick, self.fall_time
+        )
+    }
+}
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShadowInfo {
+    #[pyo3(get)]
+    location: PyVec3A,
+    #[pyo3(get)]
+    facing: PyVec3A,
+    #[pyo3(get)]
+    ball_time: Option<f32>,
+    #[pyo3(get)]
+    time: Option<f32>,
+}
+
+impl From<ShadowPlan> for ShadowInfo {
+    #[inline]
+    fn from(plan: ShadowPlan) -> Self {
+        Self {
+            location: get_tuple_from_vec3(plan.location),
+            facing: get_tuple_from_vec3(plan.facing),
+            ball_time: plan.ball_time,
+            time: plan.time,
+        }
+    }
+}
+
+#[pymethods]
+impl ShadowInfo {
+    #[inline]
+    fn __str__(&self) -> String {
+        format!("Shadow location: {:?}, ball time: {:?}, time to get there: {:?}", self.location, self.ball_time, self.time)
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "ShadowInfo(location={:?}, facing={:?}, ball_time={:?}, time={:?})",
+            self.location, self.facing, self.ball_time, self.time
+        )
+    }
+}
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KickoffInfo {
+    #[pyo3(get)]
+    spawn: KickoffSpawn,
+    #[pyo3(get)]
+    target: PyVec3A,
+    #[pyo3(get)]
+    boost_time: f32,
+    #[pyo3(get)]
+    dodge_time: Option<f32>,
+    #[pyo3(get)]
+    arrival_time: Option<f32>,
+    #[pyo3(get)]
+    opponent_arrival_time: Option<f32>,
+    #[pyo3(get)]
+    outcome: KickoffOutcome,
+}
+
+impl From<KickoffPlan> for KickoffInfo {
+    #[inline]
+    fn from(plan: KickoffPlan) -> Self {
+        Self {
+            spawn: plan.spawn,
+            target: get_tuple_from_vec3(plan.target),
+            boost_time: plan.boost_time,
+            dodge_time: plan.dodge_time,
+            arrival_time: plan.arrival_time,
+            opponent_arrival_time: plan.opponent_arrival_time,
+            outcome: plan.outcome,
+        }
+    }
+}
+
+#[pymethods]
+impl KickoffInfo {
+    #[inline]
+    fn __str__(&self) -> String {
+        format!(
+            "{:?} kickoff, dodge at: {:?}, arrival: {:?}, opponent arrival: {:?}, outcome: {:?}",
+            self.spawn, self.dodge_time, self.arrival_time, self.opponent_arrival_time, self.outcome
+        )
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "KickoffInfo(spawn={:?}, target={:?}, boost_time={}, dodge_time={:?}, arrival_time={:?}, opponent_arrival_time={:?}, outcome={:?})",
+            self.spawn, self.target, self.boost_time, self.dodge_time, self.arrival_time, self.opponent_arrival_time, self.outcome
+        )
+    }
+}
+
+/// (alpha, red, green, blue), the same order as RLBot's `renderer.create_color`
+pub type RenderColor = (u8, u8, u8, u8);
+
+#[pyclass(frozen)]
+#[derive(Clone)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderLine {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    points: Vec<PyVec3A>,
+    #[pyo3(get)]
+    color: RenderColor,
+}
+
+impl RenderLine {
+    #[inline]
+    pub fn new(name: &str, points: Vec<PyVec3A>, color: RenderColor) -> Self {
+        Self {
+            name: name.to_string(),
+            points,
+            color,
+        }
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub const fn get_color(&self) -> RenderColor {
+        self.color
+    }
+}
+
+#[pymethods]
+impl RenderLine {
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!("RenderLine(name={:?}, points=[{} items], color={:?})", self.name, self.points.len(), self.color)
+    }
+}
+
+#[pyclass(frozen)]
+#[derive(Clone)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderMarker {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    location: PyVec3A,
+    #[pyo3(get)]
+    size: f32,
+    #[pyo3(get)]
+    color: RenderColor,
+}
+
+impl RenderMarker {
+    #[inline]
+    pub fn new(name: &str, location: Vec3A, size: f32, color: RenderColor) -> Self {
+        Self {
+            name: name.to_string(),
+            location: get_tuple_from_vec3(location),
+            size,
+            color,
+        }
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[pymethods]
+impl RenderMarker {
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "RenderMarker(name={:?}, location={:?}, size={}, color={:?})",
+            self.name, self.location, self.size, self.color
+        )
+    }
+}
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShotRender {
+    #[pyo3(get)]
+    lines: Vec<RenderLine>,
+    #[pyo3(get)]
+    markers: Vec<RenderMarker>,
+}
+
+impl ShotRender {
+    #[inline]
+    pub const fn new(lines: Vec<RenderLine>, markers: Vec<RenderMarker>) -> Self {
+        Self { lines, markers }
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub fn get_lines(&self) -> &[RenderLine] {
+        &self.lines
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub fn get_markers(&self) -> &[RenderMarker] {
+        &self.markers
+    }
+}
+
+#[pymethods]
+impl ShotRender {
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!("ShotRender(lines=[{} items], markers=[{} items])", self.lines.len(), self.markers.len())
+    }
+}
+
+#[pyclass(frozen)]
+#[allow(dead_code)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShotControls {
+    #[pyo3(get)]
+    throttle: f32,
+    #[pyo3(get)]
+    steer: f32,
+    #[pyo3(get)]
+    boost: bool,
+    #[pyo3(get)]
+    handbrake: bool,
+    #[pyo3(get)]
+    cross_track_error: f32,
+    #[pyo3(get)]
+    jump: bool,
+    #[pyo3(get)]
+    pitch: f32,
+    #[pyo3(get)]
+    yaw: f32,
+    #[pyo3(get)]
+    roll: f32,
+    #[pyo3(get)]
+    revalidate: bool,
+}
+
+impl From<&ShotControls> for ControllerState {
+    #[inline]
+    fn from(controls: &ShotControls) -> Self {
+        Self {
+            throttle: controls.throttle,
+            steer: controls.steer,
+            pitch: controls.pitch,
+            yaw: controls.yaw,
+            roll: controls.roll,
+            jump: controls.jump,
+            boost: controls.boost,
+            handbrake: controls.handbrake,
+        }
+    }
+}
+
+#[pymethods]
+impl ShotControls {
+    #[inline]
+    fn __str__(&self) -> String {
+        format!(
+            "Throttle: {:.2}, steer: {:.2}, boost: {}, handbrake: {}, cross track error: {:.0}, jump: {}, pitch: {:.2}, yaw: {:.2}, roll: {:.2}, revalidate: {}",
+            self.throttle, self.steer, self.boost, self.handbrake, self.cross_track_error, self.jump, self.pitch, self.yaw, self.roll, self.revalidate
+        )
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "ShotControls(throttle={}, steer={}, boost={}, handbrake={}, cross_track_error={}, jump={}, pitch={}, yaw={}, roll={}, revalidate={})",
+            self.throttle, self.steer, self.boost, self.handbrake, self.cross_track_error, self.jump, self.pitch, self.yaw, self.roll, self.revalidate
+        )
+    }
+}
+
+impl ShotControls {
+    pub fn get_from_ground(car: &Car, shot: &GroundBasedShot, shot_info: &AdvancedShotInfo, mutators: Mutators, time_remaining: f32) -> Self {
+        let direction = if shot.is_forwards { 1. } else { -1. };
+        let distance_remaining = shot_info.get_distance_remaining();
+        let distance_along = shot.distances.iter().sum::<f32>() - distance_remaining;
+        let is_curved = PathType::CCC.contains(&shot.path.type_);
+
+        let speed = car.local_velocity.x;
+        let powersliding = shot.powerslide.map_or(false, |powerslide| distance_along < powerslide.length());
+
+        let (throttle, boost) = if powersliding {
+            (0., false)
+        } else {
+            let r = distance_remaining * direction / time_remaining.max(SIMULATION_DT);
+            let target_speed = turn_speed_limit(&shot.distances, shot.max_turn_speed, distance_along, is_curved).map_or(r, |limit| r.min(limit));
+
+            speed_controller(speed, target_speed, mutators.usable_boost(car.boost))
+        };
+
+        let (x, y, z) = shot_info.get_final_target();
+        let (steer, handbrake) = pure_pursuit_steer(car, Vec3A::new(x, y, z));
+
+        // positive when the car is to the right of the path
+        let path_yaw = if distance_along < shot.distances[0] + shot.distances[1] + shot.distances[2] {
+            shot.sample(distance_along).rot
+        } else {
+            shot.direction.y.atan2(shot.direction.x)
+        };
+        let (x, y, _) = shot_info.get_current_path_point();
+        let cross_track_error = (flatten(car.location) - Vec3A::new(x, y, 0.)).dot(Vec3A::new(-path_yaw.sin(), path_yaw.cos(), 0.));
+
+        Self {
+            throttle,
+            steer,
+            handbrake: handbrake || powersliding,
+            cross_track_error,
+            boost,
+            jump: false,
+            pitch: 0.,
+            yaw: 0.,
+            roll: 0.,
+            revalidate: false,
+        }
+    }
+
+    pub fn get_from_air(car: &Car, shot: &AirBasedShot, gravity: Vec3A, mutators: Mutators, time_remaining: f32) -> Self {
+        let jump = should_jump(car, shot.jump_type, shot.double_jump_delay);
+        let controls = if car.airborne {
+            aerial_controls(
+                &AirState::from_car(car),
+                gravity,
+                shot.final_target,
+                time_remaining,
+                mutators,
+                mutators.usable_boost(car.boost),
+            )
+        } else {
+            AerialControls::default()
+        };
+
+        Self {
+            throttle: controls.throttle,
+            steer: controls.yaw,
+            boost: controls.boost,
+            handbrake: false,
+            cross_track_error: 0.,
+            jump,
+            pitch: controls.pitch,
+            yaw: controls.yaw,
+            roll: controls.roll,
+            revalidate: !air::is_still_valid(car, shot, gravity, mutators, time_remaining.max(SIMULATION_DT)),
+        }
+    }
+}
+
+#[pyclass(name = "CarSim")]
+pub struct PyCarSim {
+    sim: car_sim::CarSim,
+}
+
+impl PyCarSim {
+    #[inline]
+    pub const fn from(sim: car_sim::CarSim) -> Self {
+        Self { sim }
+    }
+}
+
+#[pymethods]
+impl PyCarSim {
+    #[getter]
+    const fn time(&self) -> f32 {
+        self.sim.time
+    }
+
+    #[getter]
+    const fn location(&self) -> PyVec3A {
+        get_tuple_from_vec3(self.sim.state.location)
+    }
+
+    #[getter]
+    const fn velocity(&self) -> PyVec3A {
+        get_tuple_from_vec3(self.sim.state.velocity)
+    }
+
+    #[getter]
+    const fn angular_velocity(&self) -> PyVec3A {
+        get_tuple_from_vec3(self.sim.state.angular_velocity)
+    }
+
+    #[getter]
+    const fn forward(&self) -> PyVec3A {
+        get_tuple_from_vec3(self.sim.state.forward)
+    }
+
+    #[getter]
+    const fn right(&self) -> PyVec3A {
+        get_tuple_from_vec3(self.sim.state.right)
+    }
+
+    #[getter]
+    const fn up(&self) -> PyVec3A {
+        get_tuple_from_vec3(self.sim.state.up)
+    }
+
+    #[getter]
+    const fn boost(&self) -> f32 {
+        self.sim.boost
+    }
+
+    #[getter]
+    const fn on_ground(&self) -> bool {
+        self.sim.on_ground
+    }
+
+    /// Simulate the car for the given number of ticks with the same controls
+    fn step(&mut self, controls: ControllerState, ticks: Option<usize>) {
+        for _ in 0..ticks.unwrap_or(1) {
+            self.sim.step(&controls);
+        }
+    }
+
+    #[inline]
+    fn __repr__(&self) -> String {
+        format!(
+            "CarSim(time={}, location={:?}, velocity={:?}, boost={}, on_ground={})",
+            self.sim.time,
+            self.location(),
+            self.velocity(),
+            self.sim.boost,
+            self.sim.on_ground
+        )
+    }
+}
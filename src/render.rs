@@ -0,0 +1,161 @@
+use crate::{
+    car::Car,
+    pytypes::{RenderColor, RenderLine, RenderMarker, ShotRender},
+    shot::{Shot, TargetLocation},
+    utils::get_tuple_from_vec3,
+};
+use glam::Vec3A;
+use rl_ball_sym::simulation::ball::Ball;
+
+/// How high above the floor ground paths are drawn so they don't clip into it
+const PATH_HEIGHT: f32 = 20.;
+const SHOT_VECTOR_LENGTH: f32 = 500.;
+const MARKER_SIZE: f32 = 10.;
+
+const SEGMENT_COLORS: [RenderColor; 3] = [(255, 255, 0, 0), (255, 0, 255, 0), (255, 0, 0, 255)];
+const STRAIGHT_COLOR: RenderColor = (255, 255, 255, 255);
+const SHOT_VECTOR_COLOR: RenderColor = (255, 255, 255, 0);
+const CONTACT_COLOR: RenderColor = (255, 255, 0, 255);
+const LANDING_COLOR: RenderColor = (255, 0, 255, 255);
+const POST_COLOR: RenderColor = (255, 0, 255, 0);
+const POST_MISS_COLOR: RenderColor = (255, 255, 0, 0);
+const AVOID_COLOR: RenderColor = (255, 255, 128, 0);
+
+fn path_points(samples: &[Vec3A]) -> Vec<(f32, f32, f32)> {
+    samples.iter().map(|sample| get_tuple_from_vec3(Vec3A::new(sample.x, sample.y, PATH_HEIGHT))).collect()
+}
+
+/// Get the lines and markers to draw for a shot
+///
+/// `ball` is only used for its radii, so the corrected posts match the ones that the shot was found with
+pub fn shot_render(car: &Car, shot: &Shot, location: Option<&TargetLocation>, ball: &Ball) -> ShotRender {
+    let mut lines = Vec::new();
+    let mut markers = Vec::new();
+
+    let ball_location = shot.ball_location();
+
+    let shot_vector = match shot {
+        Shot::GroundBased(shot) => {
+            for (i, (samples, color)) in shot.samples.iter().zip(SEGMENT_COLORS).enumerate() {
+                lines.push(RenderLine::new(&format!("segment_{i}"), path_points(samples), color));
+            }
+
+            lines.push(RenderLine::new("final_straight", path_points(&shot.samples[3]), STRAIGHT_COLOR));
+
+            shot.direction
+        }
+        Shot::AirBased(shot) => {
+            let points = vec![get_tuple_from_vec3(car.location), get_tuple_from_vec3(shot.final_target)];
+            lines.push(RenderLine::new("final_straight", points, STRAIGHT_COLOR));
+
+            (ball_location - shot.final_target).normalize_or_zero()
+        }
+    };
+
+    let shot_vector_end = ball_location + shot_vector * SHOT_VECTOR_LENGTH;
+    lines.push(RenderLine::new(
+        "shot_vector",
+        vec![get_tuple_from_vec3(ball_location), get_tuple_from_vec3(shot_vector_end)],
+        SHOT_VECTOR_COLOR,
+    ));
+
+    markers.push(RenderMarker::new("contact_point", ball_location - shot_vector * ball.radius(), MARKER_SIZE, CONTACT_COLOR));
+    markers.push(RenderMarker::new("landing_point", car.landing_location, MARKER_SIZE, LANDING_COLOR));
+
+    if let Some(location) = location {
+        let post_info = location.post_correction(ball_location, ball.collision_radius());
+
+        // the edges of a cone that the ball has to stay out of shouldn't look like a goal to shoot at
+        let (line_name, [left_name, right_name], color) = if post_info.avoid {
+            ("avoided_cone", ["left_edge", "right_edge"], AVOID_COLOR)
+        } else {
+            ("corrected_goal", ["left_post", "right_post"], if post_info.fits { POST_COLOR } else { POST_MISS_COLOR })
+        };
+
+        lines.push(RenderLine::new(
+            line_name,
+            vec![get_tuple_from_vec3(post_info.target_left), get_tuple_from_vec3(post_info.target_right)],
+            color,
+        ));
+        markers.push(RenderMarker::new(left_name, post_info.target_left, MARKER_SIZE, color));
+        markers.push(RenderMarker::new(right_name, post_info.target_right, MARKER_SIZE, color));
+    }
+
+    ShotRender::new(lines, markers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shot_render, AVOID_COLOR};
+    use crate::{
+        analyzer::Analyzer,
+        car::get_one,
+        pytypes::ShotType,
+        shot::{GroundBasedShot, Shot, TargetLocation},
+        Mutators,
+    };
+    use glam::Vec3A;
+    use rl_ball_sym::simulation::ball::Ball;
+    use std::f32::consts::PI;
+
+    fn ground_shot() -> Shot {
+        let car = get_one();
+        let analyzer = Analyzer::new((None, None), Vec3A::new(0., 0., -650.), true, true, true, true, &car);
+
+        let time_remaining = 2.;
+        let mut ball = Ball::initialize_soccar();
+        ball.update(time_remaining, car.location + car.forward * 2000. + Vec3A::new(0., 0., 73.), Vec3A::ZERO, Vec3A::ZERO);
+
+        let target_info = analyzer.target(&ball, car.forward, time_remaining, 240, ShotType::Ground, Mutators::new()).unwrap();
+        GroundBasedShot::from(&ball, &target_info).into()
+    }
+
+    #[test]
+    pub fn render_ground_shot() {
+        let render = shot_render(&get_one(), &ground_shot(), None, &Ball::initialize_soccar());
+
+        let line_names: Vec<&str> = render.get_lines().iter().map(|line| line.get_name()).collect();
+        assert_eq!(line_names, ["segment_0", "segment_1", "segment_2", "final_straight", "shot_vector"]);
+
+        // there's no target location, so there are no posts to draw
+        let marker_names: Vec<&str> = render.get_markers().iter().map(|marker| marker.get_name()).collect();
+        assert_eq!(marker_names, ["contact_point", "landing_point"]);
+    }
+
+    #[test]
+    pub fn render_posts() {
+        let car = get_one();
+        let location = TargetLocation::new(Vec3A::new(800., 5120., 0.), Vec3A::new(-800., 5120., 0.));
+        let render = shot_render(&car, &ground_shot(), Some(&location), &Ball::initialize_soccar());
+
+        assert_eq!(render.get_lines().last().map(|line| line.get_name()), Some("corrected_goal"));
+
+        let marker_names: Vec<&str> = render.get_markers().iter().map(|marker| marker.get_name()).collect();
+        assert_eq!(marker_names, ["contact_point", "landing_point", "left_post", "right_post"]);
+    }
+
+    #[test]
+    pub fn render_avoided_cone() {
+        let car = get_one();
+        let shot = ground_shot();
+        let ball_location = shot.ball_location();
+
+        // the tolerance is clamped, so the edges stay on their own sides of the point instead of wrapping around
+        let location = TargetLocation::AwayFromPoint {
+            point: ball_location + car.right * 2000.,
+            tolerance_angle: PI,
+        };
+        let render = shot_render(&car, &shot, Some(&location), &Ball::initialize_soccar());
+
+        let avoided = render.get_lines().last().unwrap();
+        assert_eq!(avoided.get_name(), "avoided_cone");
+        assert_eq!(avoided.get_color(), AVOID_COLOR);
+
+        let marker_names: Vec<&str> = render.get_markers().iter().map(|marker| marker.get_name()).collect();
+        assert_eq!(marker_names, ["contact_point", "landing_point", "left_edge", "right_edge"]);
+
+        let post_info = location.post_correction(ball_location, Ball::initialize_soccar().collision_radius());
+        assert!((post_info.target_left - ball_location).dot(car.right) > 0.);
+        assert!((post_info.target_right - ball_location).dot(car.right) > 0.);
+    }
+}
@@ -0,0 +1,886 @@
+use crate::{
+    add_target,
+    car::Car,
+    load_dropshot, load_hoops, load_soccar, load_soccar_throwback,
+    pytypes::{GameBall, GameCar, GameCollisionShape, GameInfo, GamePacket, GamePhysics, GameRot, GameVec, Hitbox, TargetOptions},
+    remove_target,
+    shot::{Target, TargetLocation},
+    update_game, BoostAmount, Mutators, ARENA, BALL, BALL_STRUCT, CARS, GAME, GAME_TIME, GRAVITY, LAST_TICK, MUTATORS, RECORDING, TARGETS,
+};
+use combo_vec::{rearr, ReArr};
+use glam::Vec3A;
+use rl_ball_sym::simulation::{
+    ball::{Ball, BallPrediction},
+    game::Game,
+};
+use std::mem;
+
+/// The start of every replay file, followed by the format version
+const MAGIC: &[u8; 4] = b"VXRP";
+const VERSION: u8 = 3;
+
+/// One tick of a recorded game
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub packet: GamePacket,
+    pub cars: Vec<GameCar>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arena {
+    Soccar,
+    Dropshot,
+    Hoops,
+    SoccarThrowback,
+}
+
+impl Arena {
+    const fn from(item: u8) -> Option<Self> {
+        match item {
+            0 => Some(Self::Soccar),
+            1 => Some(Self::Dropshot),
+            2 => Some(Self::Hoops),
+            3 => Some(Self::SoccarThrowback),
+            _ => None,
+        }
+    }
+
+    pub fn load(self) {
+        match self {
+            Self::Soccar => load_soccar(),
+            Self::Dropshot => load_dropshot(),
+            Self::Hoops => load_hoops(),
+            Self::SoccarThrowback => load_soccar_throwback(),
+        }
+    }
+}
+
+/// Everything that can change the analyzer's state or the result of a shot search
+#[derive(Clone, Debug)]
+pub enum Event {
+    Load(Arena),
+    Tick {
+        frame: Frame,
+        prediction_time: f32,
+    },
+    Mutators(Mutators),
+    /// `location` is None for `new_any_target`
+    NewTarget {
+        location: Option<TargetLocation>,
+        car_index: usize,
+        options: Option<TargetOptions>,
+    },
+    ConfirmTarget(usize),
+    RemoveTarget(usize),
+    /// A call to `get_shot_with_target`, with the time of the shot that was found
+    ShotQuery {
+        target_index: usize,
+        temporary: Option<bool>,
+        may_ground_shot: Option<bool>,
+        may_jump_shot: Option<bool>,
+        may_double_jump_shot: Option<bool>,
+        may_aerial_shot: Option<bool>,
+        only: Option<bool>,
+        result: Option<f32>,
+    },
+}
+
+const LOAD: u8 = 0;
+const TICK: u8 = 1;
+const MUTATORS_SET: u8 = 2;
+const NEW_TARGET: u8 = 3;
+const NEW_ANY_TARGET: u8 = 4;
+const CONFIRM_TARGET: u8 = 5;
+const REMOVE_TARGET: u8 = 6;
+const SHOT_QUERY: u8 = 7;
+const NEW_WINDOW_TARGET: u8 = 8;
+const NEW_POINT_TARGET: u8 = 9;
+const NEW_AREA_TARGET: u8 = 10;
+const NEW_AWAY_TARGET: u8 = 11;
+const NEW_AWAY_FROM_POINT_TARGET: u8 = 12;
+
+/// Start a new replay file
+pub fn write_header(out: &mut Vec<u8>) {
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+}
+
+/// Start a new recording from the current state, with the arena and mutators that are loaded, the last tick, and the targets that already exist
+///
+/// The targets are added in the same slots that they're in, so the target indices in the rest of the recording still point to them
+pub fn start_recording() {
+    let mut out = Vec::new();
+    write_header(&mut out);
+
+    if let Some(arena) = *ARENA.read().unwrap() {
+        write_event(&mut out, &Event::Load(arena));
+    }
+
+    write_event(&mut out, &Event::Mutators(*MUTATORS.read().unwrap()));
+
+    if let Some((frame, prediction_time)) = LAST_TICK.read().unwrap().clone() {
+        write_event(&mut out, &Event::Tick { frame, prediction_time });
+    }
+
+    let targets = TARGETS.read().unwrap();
+    let num_slots = targets.iter().enumerate().filter(|(_, target)| target.is_some()).last().map_or(0, |(i, _)| i + 1);
+
+    // empty slots before the last target are filled with placeholders for a car that's known to exist, and removed once every target has been added
+    let placeholder_car = targets.iter().flatten().next().map_or(0, |target| target.car_index);
+
+    for (i, target) in targets.iter().take(num_slots).enumerate() {
+        write_event(
+            &mut out,
+            &match target {
+                Some(target) => Event::NewTarget {
+                    location: target.location.clone(),
+                    car_index: target.car_index,
+                    options: Some(TargetOptions::from(target.options)),
+                },
+                None => Event::NewTarget {
+                    location: None,
+                    car_index: placeholder_car,
+                    options: None,
+                },
+            },
+        );
+
+        if target.as_ref().map_or(false, Target::is_confirmed) {
+            write_event(&mut out, &Event::ConfirmTarget(i));
+        }
+    }
+
+    for (i, _) in targets.iter().take(num_slots).enumerate().filter(|(_, target)| target.is_none()) {
+        write_event(&mut out, &Event::RemoveTarget(i));
+    }
+
+    *RECORDING.write().unwrap() = Some(out);
+}
+
+/// Add the event to the current recording, if there is one
+///
+/// The event is only built when something is being recorded
+pub fn record(event: impl FnOnce() -> Event) {
+    if let Some(out) = RECORDING.write().unwrap().as_mut() {
+        write_event(out, &event());
+    }
+}
+
+fn write_f32(out: &mut Vec<u8>, value: f32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_index(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn write_option_index(out: &mut Vec<u8>, value: Option<usize>) {
+    write_index(out, value.unwrap_or(u32::MAX as usize));
+}
+
+fn write_option_bool(out: &mut Vec<u8>, value: Option<bool>) {
+    out.push(value.map_or(0, |value| 1 + u8::from(value)));
+}
+
+fn write_vec(out: &mut Vec<u8>, vec: GameVec) {
+    write_f32(out, vec.x);
+    write_f32(out, vec.y);
+    write_f32(out, vec.z);
+}
+
+fn write_physics(out: &mut Vec<u8>, physics: &GamePhysics) {
+    write_vec(out, physics.location);
+    write_vec(out, physics.velocity);
+    write_vec(out, physics.angular_velocity);
+    write_f32(out, physics.rotation.pitch);
+    write_f32(out, physics.rotation.yaw);
+    write_f32(out, physics.rotation.roll);
+}
+
+fn write_frame(out: &mut Vec<u8>, frame: &Frame) {
+    let Frame { packet, cars } = frame;
+
+    write_f32(out, packet.game_info.seconds_elapsed);
+    write_f32(out, packet.game_info.world_gravity_z);
+    write_physics(out, &packet.game_ball.physics);
+    // update_game doesn't record packets with unknown shapes
+    write_f32(out, packet.game_ball.collision_shape.get_radius().unwrap_or_default());
+
+    out.push(cars.len() as u8);
+
+    for car in cars {
+        write_physics(out, &car.physics);
+        write_f32(out, car.hitbox.length);
+        write_f32(out, car.hitbox.width);
+        write_f32(out, car.hitbox.height);
+        write_vec(out, car.hitbox_offset);
+        out.push(car.boost);
+        out.push(u8::from(car.jumped) | u8::from(car.double_jumped) << 1 | u8::from(car.is_demolished) << 2 | u8::from(car.has_wheel_contact) << 3);
+    }
+}
+
+fn write_options(out: &mut Vec<u8>, options: Option<TargetOptions>) {
+    let options = options.unwrap_or_default();
+
+    write_option_index(out, options.min_slice);
+    write_option_index(out, options.max_slice);
+    write_option_bool(out, options.use_absolute_max_values);
+    write_option_bool(out, options.all);
+    write_option_bool(out, options.simulate_aerials);
+    write_option_bool(out, options.debug);
+}
+
+/// Add an event to the end of a replay file
+pub fn write_event(out: &mut Vec<u8>, event: &Event) {
+    match event {
+        Event::Load(arena) => {
+            out.push(LOAD);
+            out.push(*arena as u8);
+        }
+        Event::Tick { frame, prediction_time } => {
+            out.push(TICK);
+            write_f32(out, *prediction_time);
+            write_frame(out, frame);
+        }
+        Event::Mutators(mutators) => {
+            out.push(MUTATORS_SET);
+            out.push(mutators.boost_amount as u8);
+            write_f32(out, mutators.boost_accel);
+        }
+        Event::NewTarget { location, car_index, options } => {
+            match location {
+                Some(TargetLocation::Posts { left, right, window }) => {
+                    out.push(if window.is_some() { NEW_WINDOW_TARGET } else { NEW_TARGET });
+                    write_vec(out, (*left).into());
+                    write_vec(out, (*right).into());
+
+                    if let Some((bottom, top)) = window {
+                        write_f32(out, *bottom);
+                        write_f32(out, *top);
+                    }
+                }
+                Some(TargetLocation::Point { point, tolerance_angle }) => {
+                    out.push(NEW_POINT_TARGET);
+                    write_vec(out, (*point).into());
+                    write_f32(out, *tolerance_angle);
+                }
+                Some(TargetLocation::AwayFromPosts { left, right }) => {
+                    out.push(NEW_AWAY_TARGET);
+                    write_vec(out, (*left).into());
+                    write_vec(out, (*right).into());
+                }
+                Some(TargetLocation::AwayFromPoint { point, tolerance_angle }) => {
+                    out.push(NEW_AWAY_FROM_POINT_TARGET);
+                    write_vec(out, (*point).into());
+                    write_f32(out, *tolerance_angle);
+                }
+                Some(TargetLocation::Area(polygon)) => {
+                    out.push(NEW_AREA_TARGET);
+                    write_index(out, polygon.len());
+                    polygon.iter().for_each(|corner| write_vec(out, (*corner).into()));
+                }
+                None => out.push(NEW_ANY_TARGET),
+            }
+
+            write_index(out, *car_index);
+            write_options(out, *options);
+        }
+        Event::ConfirmTarget(target_index) => {
+            out.push(CONFIRM_TARGET);
+            write_index(out, *target_index);
+        }
+        Event::RemoveTarget(target_index) => {
+            out.push(REMOVE_TARGET);
+            write_index(out, *target_index);
+        }
+        Event::ShotQuery {
+            target_index,
+            temporary,
+            may_ground_shot,
+            may_jump_shot,
+            may_double_jump_shot,
+            may_aerial_shot,
+            only,
+            result,
+        } => {
+            out.push(SHOT_QUERY);
+            write_index(out, *target_index);
+            [temporary, may_ground_shot, may_jump_shot, may_double_jump_shot, may_aerial_shot, only]
+                .into_iter()
+                .for_each(|value| write_option_bool(out, *value));
+            write_f32(out, result.unwrap_or(-1.));
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.take(4).map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn index(&mut self) -> Option<usize> {
+        self.take(4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    fn option_index(&mut self) -> Option<Option<usize>> {
+        self.index().map(|index| (index != u32::MAX as usize).then_some(index))
+    }
+
+    fn option_bool(&mut self) -> Option<Option<bool>> {
+        match self.u8()? {
+            0 => Some(None),
+            1 => Some(Some(false)),
+            2 => Some(Some(true)),
+            _ => None,
+        }
+    }
+
+    fn vec(&mut self) -> Option<GameVec> {
+        Some(GameVec {
+            x: self.f32()?,
+            y: self.f32()?,
+            z: self.f32()?,
+        })
+    }
+
+    fn physics(&mut self) -> Option<GamePhysics> {
+        Some(GamePhysics {
+            location: self.vec()?,
+            velocity: self.vec()?,
+            angular_velocity: self.vec()?,
+            rotation: GameRot {
+                pitch: self.f32()?,
+                yaw: self.f32()?,
+                roll: self.f32()?,
+            },
+        })
+    }
+
+    fn car(&mut self) -> Option<GameCar> {
+        let physics = self.physics()?;
+        let hitbox = Hitbox {
+            length: self.f32()?,
+            width: self.f32()?,
+            height: self.f32()?,
+        };
+        let hitbox_offset = self.vec()?;
+        let boost = self.u8()?;
+        let flags = self.u8()?;
+
+        Some(GameCar {
+            physics,
+            hitbox,
+            hitbox_offset,
+            boost,
+            jumped: flags & 1 != 0,
+            double_jumped: flags & 2 != 0,
+            is_demolished: flags & 4 != 0,
+            has_wheel_contact: flags & 8 != 0,
+        })
+    }
+
+    fn frame(&mut self) -> Option<Frame> {
+        let game_info = GameInfo {
+            seconds_elapsed: self.f32()?,
+            world_gravity_z: self.f32()?,
+        };
+        let game_ball = GameBall {
+            physics: self.physics()?,
+            collision_shape: GameCollisionShape::from_radius(self.f32()?),
+        };
+
+        let num_cars = usize::from(self.u8()?);
+        let cars = (0..num_cars).map(|_| self.car()).collect::<Option<Vec<_>>>()?;
+
+        Some(Frame {
+            packet: GamePacket { game_info, game_ball, num_cars },
+            cars,
+        })
+    }
+
+    fn options(&mut self) -> Option<TargetOptions> {
+        Some(TargetOptions {
+            min_slice: self.option_index()?,
+            max_slice: self.option_index()?,
+            use_absolute_max_values: self.option_bool()?,
+            all: self.option_bool()?,
+            simulate_aerials: self.option_bool()?,
+            debug: self.option_bool()?,
+        })
+    }
+
+    fn event(&mut self) -> Option<Event> {
+        Some(match self.u8()? {
+            LOAD => Event::Load(Arena::from(self.u8()?)?),
+            TICK => {
+                let prediction_time = self.f32()?;
+                Event::Tick {
+                    frame: self.frame()?,
+                    prediction_time,
+                }
+            }
+            MUTATORS_SET => Event::Mutators(Mutators {
+                boost_amount: BoostAmount::from(self.u8()?),
+                boost_accel: self.f32()?,
+            }),
+            tag @ (NEW_TARGET | NEW_WINDOW_TARGET | NEW_POINT_TARGET | NEW_AREA_TARGET | NEW_AWAY_TARGET | NEW_AWAY_FROM_POINT_TARGET | NEW_ANY_TARGET) => Event::NewTarget {
+                location: match tag {
+                    NEW_TARGET => Some(TargetLocation::new(self.vec()?.into(), self.vec()?.into())),
+                    NEW_WINDOW_TARGET => Some(TargetLocation::window(self.vec()?.into(), self.vec()?.into(), self.f32()?, self.f32()?)),
+                    NEW_POINT_TARGET => Some(TargetLocation::Point {
+                        point: self.vec()?.into(),
+                        tolerance_angle: self.f32()?,
+                    }),
+                    NEW_AREA_TARGET => {
+                        let num_corners = self.index()?;
+                        Some(TargetLocation::Area((0..num_corners).map(|_| self.vec().map(Vec3A::from)).collect::<Option<_>>()?))
+                    }
+                    NEW_AWAY_TARGET => Some(TargetLocation::AwayFromPosts {
+                        left: self.vec()?.into(),
+                        right: self.vec()?.into(),
+                    }),
+                    NEW_AWAY_FROM_POINT_TARGET => Some(TargetLocation::AwayFromPoint {
+                        point: self.vec()?.into(),
+                        tolerance_angle: self.f32()?,
+                    }),
+                    _ => None,
+                },
+                car_index: self.index()?,
+                options: Some(self.options()?),
+            },
+            CONFIRM_TARGET => Event::ConfirmTarget(self.index()?),
+            REMOVE_TARGET => Event::RemoveTarget(self.index()?),
+            SHOT_QUERY => Event::ShotQuery {
+                target_index: self.index()?,
+                temporary: self.option_bool()?,
+                may_ground_shot: self.option_bool()?,
+                may_jump_shot: self.option_bool()?,
+                may_double_jump_shot: self.option_bool()?,
+                may_aerial_shot: self.option_bool()?,
+                only: self.option_bool()?,
+                result: Some(self.f32()?).filter(|time| *time >= 0.),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Read every event in a replay file, or None if the file is corrupted or from a different version
+pub fn read_replay(bytes: &[u8]) -> Option<Vec<Event>> {
+    let mut reader = Reader { bytes };
+
+    if reader.take(MAGIC.len())? != MAGIC || reader.u8()? != VERSION {
+        return None;
+    }
+
+    let mut events = Vec::new();
+
+    while !reader.bytes.is_empty() {
+        events.push(reader.event()?);
+    }
+
+    Some(events)
+}
+
+/// A shot search from a replay, with what was found when it was recorded and what was found when it was replayed
+#[derive(Clone, Copy, Debug)]
+pub struct ReplayedQuery {
+    pub game_time: f32,
+    pub target_index: usize,
+    pub recorded: Option<f32>,
+    pub replayed: Option<f32>,
+}
+
+/// Everything in the analyzer that a replay changes
+struct SavedState {
+    cars: ReArr<Car, 8>,
+    ball_struct: BallPrediction,
+    gravity: Vec3A,
+    game_time: f32,
+    game: Option<Game>,
+    ball: Ball,
+    mutators: Mutators,
+    targets: ReArr<Option<Target>, 16>,
+    arena: Option<Arena>,
+    recording: Option<Vec<u8>>,
+    last_tick: Option<(Frame, f32)>,
+}
+
+impl SavedState {
+    /// Take the analyzer's state, which leaves it in the same state as when the bot starts
+    fn take() -> Self {
+        Self {
+            cars: mem::replace(&mut *CARS.write().unwrap(), rearr![]),
+            ball_struct: mem::take(&mut *BALL_STRUCT.write().unwrap()),
+            gravity: mem::replace(&mut *GRAVITY.write().unwrap(), Vec3A::ZERO),
+            game_time: mem::replace(&mut *GAME_TIME.write().unwrap(), 0.),
+            game: GAME.write().unwrap().take(),
+            ball: mem::replace(&mut *BALL.write().unwrap(), Ball::const_default()),
+            mutators: mem::take(&mut *MUTATORS.write().unwrap()),
+            targets: mem::replace(&mut *TARGETS.write().unwrap(), rearr![]),
+            arena: ARENA.write().unwrap().take(),
+            recording: RECORDING.write().unwrap().take(),
+            last_tick: LAST_TICK.write().unwrap().take(),
+        }
+    }
+
+    fn restore(self) {
+        *CARS.write().unwrap() = self.cars;
+        *BALL_STRUCT.write().unwrap() = self.ball_struct;
+        *GRAVITY.write().unwrap() = self.gravity;
+        *GAME_TIME.write().unwrap() = self.game_time;
+        *GAME.write().unwrap() = self.game;
+        *BALL.write().unwrap() = self.ball;
+        *MUTATORS.write().unwrap() = self.mutators;
+        *TARGETS.write().unwrap() = self.targets;
+        *ARENA.write().unwrap() = self.arena;
+        *RECORDING.write().unwrap() = self.recording;
+        *LAST_TICK.write().unwrap() = self.last_tick;
+    }
+}
+
+/// Feed the events back through the analyzer in the order that they were recorded
+///
+/// The replay starts from the state that the analyzer is in when the bot starts, and the analyzer's own state is put back afterwards
+/// Recording is paused during the replay
+pub fn play(events: &[Event]) -> Vec<ReplayedQuery> {
+    let saved = SavedState::take();
+    let mut queries = Vec::new();
+
+    for event in events {
+        match event {
+            Event::Load(arena) => arena.load(),
+            Event::Tick { frame, prediction_time } => {
+                // errors are ignored so the replay behaves like the recording, where the bot kept going after them
+                let _ = update_game(frame.packet, &frame.cars, *prediction_time);
+            }
+            Event::Mutators(mutators) => *MUTATORS.write().unwrap() = *mutators,
+            Event::NewTarget { location, car_index, options } => {
+                let _ = add_target(location.clone(), *car_index, *options);
+            }
+            Event::ConfirmTarget(target_index) => {
+                // the confirmation worked when it was recorded, but targets that existed before the recording started don't have a shot in it
+                if let Some(Some(target)) = TARGETS.write().unwrap().get_mut(*target_index) {
+                    target.confirm();
+                }
+            }
+            Event::RemoveTarget(target_index) => remove_target(*target_index),
+            Event::ShotQuery {
+                target_index,
+                temporary,
+                may_ground_shot,
+                may_jump_shot,
+                may_double_jump_shot,
+                may_aerial_shot,
+                only,
+                result,
+            } => queries.push(ReplayedQuery {
+                game_time: *GAME_TIME.read().unwrap(),
+                target_index: *target_index,
+                recorded: *result,
+                replayed: crate::find_shot_with_target(*target_index, *temporary, *may_ground_shot, *may_jump_shot, *may_double_jump_shot, *may_aerial_shot, *only)
+                    .ok()
+                    .and_then(|shot| shot.get_time()),
+            }),
+        }
+    }
+
+    saved.restore();
+
+    queries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{play, read_replay, start_recording, Event, Frame};
+    use crate::{
+        car::get_one,
+        car_sim::CarSim,
+        confirm_target,
+        constants::*,
+        get_controls_for_shot, get_data_for_shot_with_target, get_shot_with_target, load_soccar, new_any_target,
+        pytypes::{ControllerState, GameBall, GameCollisionShape, GameInfo, GamePacket, GamePhysics, GameRot, ShotRejection, TargetOptions},
+        remove_target, update_game, Mutators, BALL, CARS, GAME, GAME_TIME, LAST_TICK, MUTATORS, RECORDING, TARGETS,
+    };
+    use glam::Vec3A;
+    use std::{fs, sync::Mutex};
+
+    /// How far the car can be from touching the ball at the time of a shot
+    const REACHED_TOLERANCE: f32 = 100.;
+
+    /// Made by `record_ground_shot`, where the car is driven by this library's own controller
+    ///
+    /// It can show that replays are deterministic, but not that shots get reached
+    const SYNTHETIC_GROUND_SHOT: &[u8] = include_bytes!("../replays/synthetic/ground_shot.vxr");
+
+    /// Where recordings of real matches go, made with `start_recording` and `stop_recording` from a bot
+    const CAPTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/replays/captures");
+
+    /// The replays share the analyzer's global state, so only one can run at a time
+    static ANALYZER: Mutex<()> = Mutex::new(());
+
+    /// Put the analyzer back into the state it's in when the bot starts, so tests don't see each other's targets, cars, or recordings
+    fn reset() {
+        TARGETS.write().unwrap().clear();
+        CARS.write().unwrap().clear();
+        *RECORDING.write().unwrap() = None;
+        *LAST_TICK.write().unwrap() = None;
+        *MUTATORS.write().unwrap() = Mutators::new();
+        load_soccar();
+    }
+
+    /// Every recording of a real match in `replays/captures`, with its file name
+    fn captures() -> Vec<(String, Vec<u8>)> {
+        let Ok(dir) = fs::read_dir(CAPTURES_DIR) else {
+            return Vec::new();
+        };
+
+        let mut captures: Vec<_> = dir
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map_or(false, |extension| extension == "vxr"))
+            .map(|path| (path.file_stem().unwrap().to_string_lossy().into_owned(), fs::read(&path).unwrap()))
+            .collect();
+        captures.sort();
+
+        captures
+    }
+
+    fn frames(events: &[Event]) -> Vec<Frame> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Tick { frame, .. } => Some(frame.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn reached_shot(frames: &[Frame], car_index: usize, time: f32) -> bool {
+        frames.iter().filter(|frame| (frame.packet.game_info.seconds_elapsed - time).abs() < 0.1).any(|frame| {
+            let car = &frame.cars[car_index];
+            let ball = &frame.packet.game_ball;
+            let distance = Vec3A::from(car.physics.location).distance(ball.physics.location.into());
+
+            distance < ball.collision_shape.get_radius().unwrap() + car.hitbox.length / 2. + REACHED_TOLERANCE
+        })
+    }
+
+    /// The shot that's found on the first frame of each recording is the one that the car in the recording goes for,
+    /// so it should stay valid for the whole recording and the car should be at the ball at the time of the shot
+    ///
+    /// Each capture should start just before the bot commits to a shot, so the first shot that's found is the one it took
+    #[test]
+    #[ignore = "needs recordings of real matches in replays/captures"]
+    pub fn replay_shots_are_reached() {
+        let _lock = ANALYZER.lock().unwrap();
+
+        let captures = captures();
+        assert!(!captures.is_empty(), "there are no recordings in {CAPTURES_DIR}");
+
+        for (name, bytes) in captures {
+            reset();
+
+            let frames = frames(&read_replay(&bytes).unwrap_or_else(|| panic!("{name} couldn't be read")));
+            update_game(frames[0].packet, &frames[0].cars, 6.).unwrap();
+
+            let target_index = new_any_target(0, None).unwrap();
+            let time = get_shot_with_target(target_index, None, None, None, None, None, None)
+                .unwrap()
+                .get_time()
+                .unwrap_or_else(|| panic!("{name}: no shot was found"));
+            confirm_target(target_index).unwrap();
+
+            for frame in frames.iter().skip(1).take_while(|frame| frame.packet.game_info.seconds_elapsed < time - 0.1) {
+                update_game(frame.packet, &frame.cars, 6.).unwrap();
+
+                if let Err(error) = get_data_for_shot_with_target(target_index) {
+                    panic!("{name}: shot at {time} was invalid at {}: {error:?}", frame.packet.game_info.seconds_elapsed);
+                }
+            }
+
+            assert!(reached_shot(&frames, 0, time), "{name}: shot at {time} wasn't reached");
+        }
+    }
+
+    #[test]
+    pub fn replays_are_deterministic() {
+        let _lock = ANALYZER.lock().unwrap();
+
+        let replays = captures().into_iter().chain([("synthetic ground_shot".to_owned(), SYNTHETIC_GROUND_SHOT.to_vec())]);
+
+        for (name, bytes) in replays {
+            reset();
+
+            let queries = play(&read_replay(&bytes).unwrap_or_else(|| panic!("{name} couldn't be read")));
+
+            assert!(!queries.is_empty(), "{name} has no shot searches");
+
+            for query in queries {
+                assert_eq!(query.recorded, query.replayed, "{name}: shot search at {} changed", query.game_time);
+            }
+        }
+    }
+
+    /// A recording that's started while targets exist still finds them at the same indices
+    #[test]
+    pub fn recording_starts_with_existing_targets() {
+        let _lock = ANALYZER.lock().unwrap();
+        reset();
+
+        let frames = frames(&read_replay(SYNTHETIC_GROUND_SHOT).unwrap());
+        update_game(frames[0].packet, &frames[0].cars, 6.).unwrap();
+
+        let removed_index = new_any_target(0, None).unwrap();
+        let target_index = new_any_target(0, None).unwrap();
+        let time = get_shot_with_target(target_index, None, None, None, None, None, None).unwrap().get_time();
+        assert!(time.is_some());
+        confirm_target(target_index).unwrap();
+        remove_target(removed_index);
+
+        start_recording();
+        update_game(frames[1].packet, &frames[1].cars, 6.).unwrap();
+        get_shot_with_target(target_index, Some(true), None, None, None, None, None).unwrap();
+        let recording = RECORDING.write().unwrap().take().unwrap();
+
+        let queries = play(&read_replay(&recording).unwrap());
+
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].target_index, target_index);
+        assert!(queries[0].replayed.is_some());
+        assert_eq!(queries[0].recorded, queries[0].replayed);
+    }
+
+    /// Replaying doesn't change the bot's own game state or targets
+    #[test]
+    pub fn replay_restores_state() {
+        let _lock = ANALYZER.lock().unwrap();
+        reset();
+
+        let frames = frames(&read_replay(SYNTHETIC_GROUND_SHOT).unwrap());
+        let frame = frames.last().unwrap();
+        update_game(frame.packet, &frame.cars, 6.).unwrap();
+        let target_index = new_any_target(0, None).unwrap();
+
+        play(&read_replay(SYNTHETIC_GROUND_SHOT).unwrap());
+
+        assert_eq!(*GAME_TIME.read().unwrap(), frame.packet.game_info.seconds_elapsed);
+        assert_eq!(CARS.read().unwrap()[0].location, Vec3A::from(frame.cars[0].physics.location));
+        assert!(matches!(TARGETS.read().unwrap().get(target_index), Some(Some(_))));
+        assert_eq!(TARGETS.read().unwrap().len(), target_index + 1);
+    }
+
+    #[test]
+    pub fn debug_reports_rejections() {
+        let _lock = ANALYZER.lock().unwrap();
+        reset();
+
+        let frames = frames(&read_replay(SYNTHETIC_GROUND_SHOT).unwrap());
+        update_game(frames[0].packet, &frames[0].cars, 6.).unwrap();
+
+        let options = TargetOptions {
+            debug: Some(true),
+            ..Default::default()
+        };
+        let target_index = new_any_target(0, Some(options)).unwrap();
+        let shot = get_shot_with_target(target_index, Some(true), None, None, None, None, None).unwrap();
+        let time = shot.get_time().unwrap();
+
+        // every slice before the shot was rejected, and the car is too far away to get to the first slices
+        assert!(shot.get_rejections().iter().all(|(slice_time, _)| *slice_time < time));
+        assert_eq!(shot.get_rejections().first().map(|(_, rejection)| *rejection), Some(ShotRejection::PathTooLong));
+    }
+
+    /// Records `replays/synthetic/ground_shot.vxr` by driving the car with `get_controls_for_shot` in `CarSim` while the ball is simulated with `rl_ball_sym`
+    ///
+    /// There is no car-ball collision, so the recording stops shortly after the car gets to the ball
+    #[test]
+    #[ignore = "overwrites replays/synthetic/ground_shot.vxr"]
+    pub fn record_ground_shot() {
+        let _lock = ANALYZER.lock().unwrap();
+        reset();
+
+        start_recording();
+
+        let gravity = Vec3A::new(0., 0., -650.);
+        let mut ball = *BALL.read().unwrap();
+        ball.update(0., Vec3A::new(500., 1000., 93.), Vec3A::new(-200., 600., 0.), Vec3A::ZERO);
+
+        let template = get_one();
+        let mut car = CarSim::from(&template, gravity, Mutators::new(), 0.);
+        car.state.location = Vec3A::new(-1500., -1000., CAR_REST_HEIGHT);
+
+        let mut target_index = None;
+        let mut touched_time = None;
+
+        while ball.time < 6. && touched_time.map_or(true, |time| ball.time < time + 0.25) {
+            let packet = GamePacket {
+                game_info: GameInfo {
+                    seconds_elapsed: ball.time,
+                    world_gravity_z: gravity.z,
+                },
+                game_ball: GameBall {
+                    physics: GamePhysics {
+                        location: ball.location.into(),
+                        velocity: ball.velocity.into(),
+                        angular_velocity: ball.angular_velocity.into(),
+                        rotation: GameRot::default(),
+                    },
+                    collision_shape: GameCollisionShape::from_radius(ball.radius()),
+                },
+                num_cars: 1,
+            };
+
+            update_game(packet, &[car.get_game_car(template.hitbox, template.hitbox_offset)], 6.).unwrap();
+
+            if target_index.is_none() {
+                let index = new_any_target(0, None).unwrap();
+                let shot = get_shot_with_target(index, None, None, None, None, None, None).unwrap();
+
+                if shot.get_time().is_some() {
+                    confirm_target(index).unwrap();
+                    target_index = Some(index);
+                }
+            }
+
+            let controls = match target_index.map(get_controls_for_shot) {
+                Some(Ok(controls)) => ControllerState::from(&controls),
+                Some(Err(_)) => {
+                    remove_target(target_index.take().unwrap());
+                    ControllerState::default()
+                }
+                None => ControllerState::default(),
+            };
+
+            car.step(&controls);
+            ball.step(GAME.read().unwrap().as_ref().unwrap(), SIMULATION_DT);
+
+            if touched_time.is_none() && car.state.location.distance(ball.location) < ball.radius() + template.hitbox.length / 2. {
+                touched_time = Some(ball.time);
+            }
+        }
+
+        let recording = RECORDING.write().unwrap().take().unwrap();
+
+        assert!(touched_time.is_some(), "the car never got to the ball");
+
+        fs::write(concat!(env!("CARGO_MANIFEST_DIR"), "/replays/synthetic/ground_shot.vxr"), recording).unwrap();
+    }
+}
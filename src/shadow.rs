@@ -0,0 +1,137 @@
+use crate::{car::Car, constants::*, ground::shortest_path_in_validate, utils::flatten};
+use dubins_paths::PosRot;
+use glam::Vec3A;
+use rl_ball_sym::simulation::ball::Ball;
+
+/// How far the car can drive by the end of each slice, going as fast as it can
+fn max_distances(car: &Car, num_slices: usize) -> Vec<f32> {
+    car.max_speed
+        .iter()
+        .take(num_slices)
+        .scan(0., |distance, speed| {
+            *distance += speed.max(0.) * SIMULATION_DT;
+            Some(*distance)
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShadowPlan {
+    pub location: Vec3A,
+    pub facing: Vec3A,
+    /// The time of the ball slice that the opponent can get to first, if they can get to it before the end of the ball prediction
+    pub ball_time: Option<f32>,
+    /// How long it will take the car to get to the location, if it can get there before the end of the ball prediction
+    pub time: Option<f32>,
+}
+
+/// Find where the car should be to stay between the ball and the goal when the opponent gets to the ball
+///
+/// `distance` is how far in front of the ball, towards the goal, the car should be
+/// If the opponent can't get to the ball at all, the car shadows the last slice of the ball prediction instead
+pub fn shadow_position(car: &Car, opponent: &Car, ball_prediction: &[Ball], game_time: f32, goal: Vec3A, distance: f32) -> Option<ShadowPlan> {
+    // the opponent has to turn to hit the ball towards the goal, just like the car would for a shot
+    let opponent_q0 = PosRot::new(flatten(opponent.landing_location), opponent.landing_yaw);
+    let reached_ball = ball_prediction
+        .iter()
+        .zip(max_distances(opponent, ball_prediction.len()))
+        .enumerate()
+        .find(|(i, (ball, max_distance))| {
+            let ball_to_goal = flatten(goal - ball.location);
+            let q1 = PosRot::new(flatten(ball.location), ball_to_goal.y.atan2(ball_to_goal.x));
+
+            shortest_path_in_validate(opponent_q0, q1, opponent.ctrms[*i], &opponent.field, *max_distance).is_ok()
+        })
+        .map(|(_, (ball, _))| ball);
+    let ball = reached_ball.or_else(|| ball_prediction.last())?;
+
+    let ball_to_goal = flatten(goal - ball.location);
+    let location = flatten(ball.location) + ball_to_goal.normalize_or_zero() * distance.min(ball_to_goal.length()) + Vec3A::new(0., 0., CAR_REST_HEIGHT);
+    let facing = flatten(ball.location - location).normalize_or_zero();
+
+    let q0 = PosRot::new(flatten(car.landing_location), car.landing_yaw);
+    let q1 = PosRot::new(flatten(location), facing.y.atan2(facing.x));
+
+    let time = max_distances(car, ball_prediction.len()).into_iter().enumerate().find_map(|(i, max_distance)| {
+        shortest_path_in_validate(q0, q1, car.ctrms[i], &car.field, max_distance)
+            .ok()
+            .map(|_| ball_prediction[i].time - game_time)
+    });
+
+    Some(ShadowPlan {
+        location,
+        facing,
+        ball_time: reached_ball.map(|ball| ball.time),
+        time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shadow_position;
+    use crate::{car::get_one, utils::flatten, Mutators};
+    use glam::Vec3A;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    pub fn shadow_between_ball_and_goal() {
+        let (game, mut ball) = rl_ball_sym::compressed::load_soccar();
+        ball.update(0., Vec3A::new(0., 0., 93.), Vec3A::ZERO, Vec3A::ZERO);
+        let ball_prediction = ball.get_ball_prediction_struct_for_time(&game, 6.);
+
+        let car = get_one();
+        let mut opponent = get_one();
+        opponent.location = Vec3A::new(0., 3000., 17.);
+        opponent.init(-650., None, ball_prediction.len(), Mutators::new());
+
+        let plan = shadow_position(&car, &opponent, &ball_prediction, 0., Vec3A::new(0., -5120., 0.), 1000.).unwrap();
+
+        assert!(flatten(plan.location - Vec3A::new(0., -1000., 0.)).length() < 1.);
+        assert!(plan.facing.y > 0.99);
+        assert!(plan.time.unwrap() > 0.);
+    }
+
+    #[test]
+    pub fn opponent_has_to_turn() {
+        let (game, mut ball) = rl_ball_sym::compressed::load_soccar();
+        ball.update(0., Vec3A::new(0., 0., 93.), Vec3A::ZERO, Vec3A::ZERO);
+        let ball_prediction = ball.get_ball_prediction_struct_for_time(&game, 6.);
+
+        let car = get_one();
+        let goal = Vec3A::new(0., -5120., 0.);
+
+        let mut facing_ball = get_one();
+        facing_ball.location = Vec3A::new(0., 3000., 17.);
+        facing_ball.yaw = -FRAC_PI_2;
+        facing_ball.init(-650., None, ball_prediction.len(), Mutators::new());
+
+        let mut facing_away = facing_ball.clone();
+        facing_away.yaw = FRAC_PI_2;
+        facing_away.init = false;
+        facing_away.init(-650., None, ball_prediction.len(), Mutators::new());
+
+        let facing_ball_plan = shadow_position(&car, &facing_ball, &ball_prediction, 0., goal, 1000.).unwrap();
+        let facing_away_plan = shadow_position(&car, &facing_away, &ball_prediction, 0., goal, 1000.).unwrap();
+
+        // an opponent that has to turn around gets to the ball later, even from the same spot
+        assert!(facing_away_plan.ball_time.unwrap() > facing_ball_plan.ball_time.unwrap());
+    }
+
+    #[test]
+    pub fn opponent_cant_reach_ball() {
+        let (game, mut ball) = rl_ball_sym::compressed::load_soccar();
+        ball.update(0., Vec3A::new(0., 0., 93.), Vec3A::ZERO, Vec3A::ZERO);
+        let ball_prediction = ball.get_ball_prediction_struct_for_time(&game, 0.5);
+
+        let car = get_one();
+        let mut opponent = get_one();
+        opponent.location = Vec3A::new(0., 5000., 17.);
+        opponent.init(-650., None, ball_prediction.len(), Mutators::new());
+
+        let plan = shadow_position(&car, &opponent, &ball_prediction, 0., Vec3A::new(0., -5120., 0.), 1000.).unwrap();
+
+        // the car still shadows the ball at the end of the prediction
+        assert!(plan.ball_time.is_none());
+        assert!(flatten(plan.location - Vec3A::new(0., -1000., 0.)).length() < 1.);
+    }
+}
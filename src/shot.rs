@@ -2,12 +2,69 @@ use crate::{
     air::{AerialJumpType, AerialTargetInfo},
     ground::GroundTargetInfo,
     pytypes::{ShotType, TargetOptions},
-    utils::{get_samples_from_line, get_samples_from_path},
+    utils::{get_samples_from_line, get_samples_from_path, PostCorrection},
 };
 use dubins_paths::{DubinsPath, PathType, PosRot};
 use glam::Vec3A;
 use rl_ball_sym::simulation::ball::Ball;
 
+/// Serde definitions for the types from `dubins_paths`, which doesn't support serde itself
+#[cfg(feature = "serde")]
+mod remote {
+    use dubins_paths::{DubinsPath, PathType, PosRot};
+    use glam::Vec3A;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    // the variants have to match the names in dubins_paths
+    #[allow(clippy::upper_case_acronyms)]
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "PathType")]
+    enum PathTypeDef {
+        LSL,
+        LSR,
+        RSL,
+        RSR,
+        RLR,
+        LRL,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "PosRot")]
+    pub struct PosRotDef {
+        pos: Vec3A,
+        rot: f32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "DubinsPath")]
+    pub struct DubinsPathDef {
+        #[serde(with = "PosRotDef")]
+        qi: PosRot,
+        rho: f32,
+        param: [f32; 3],
+        #[serde(with = "PathTypeDef")]
+        type_: PathType,
+    }
+
+    pub mod option_dubins_path {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(path: &Option<DubinsPath>, serializer: S) -> Result<S::Ok, S::Error> {
+            #[derive(Serialize)]
+            struct Wrapper<'a>(#[serde(with = "DubinsPathDef")] &'a DubinsPath);
+
+            path.as_ref().map(Wrapper).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DubinsPath>, D::Error> {
+            #[derive(Deserialize)]
+            struct Wrapper(#[serde(with = "DubinsPathDef")] DubinsPath);
+
+            Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(path)| path))
+        }
+    }
+}
+
 #[inline]
 const fn posrot_to_xy_tuple(posrot: &PosRot) -> (f32, f32) {
     let [x, y, _] = posrot.pos.to_array();
@@ -15,6 +72,7 @@ const fn posrot_to_xy_tuple(posrot: &PosRot) -> (f32, f32) {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shot {
     GroundBased(Box<GroundBasedShot>),
     AirBased(AirBasedShot),
@@ -53,10 +111,12 @@ impl From<AirBasedShot> for Shot {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AirBasedShot {
     pub time: f32,
     pub final_target: Vec3A,
     pub jump_type: AerialJumpType,
+    pub double_jump_delay: f32,
     pub ball_location: Vec3A,
 }
 
@@ -67,6 +127,7 @@ impl AirBasedShot {
             time: 0.,
             final_target: Vec3A::ZERO,
             jump_type: AerialJumpType::None,
+            double_jump_delay: 0.,
             ball_location: Vec3A::ZERO,
         }
     }
@@ -77,12 +138,14 @@ impl AirBasedShot {
             time: ball.time,
             final_target: target_info.final_target,
             jump_type: target_info.jump_type,
+            double_jump_delay: target_info.double_jump_delay,
             ball_location: ball.location,
         }
     }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroundBasedShot {
     pub time: f32,
     pub ball_location: Vec3A,
@@ -90,10 +153,16 @@ pub struct GroundBasedShot {
     pub distances: [f32; 4],
     pub all_samples: Vec<(f32, f32)>,
     pub samples: [Vec<Vec3A>; 4],
+    #[cfg_attr(feature = "serde", serde(with = "remote::DubinsPathDef"))]
     pub path: DubinsPath,
+    #[cfg_attr(feature = "serde", serde(with = "remote::option_dubins_path"))]
+    pub powerslide: Option<DubinsPath>,
+    pub max_turn_speed: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(with = "remote::PosRotDef"))]
     pub path_endpoint: PosRot,
     pub shot_type: ShotType,
     pub jump_time: Option<f32>,
+    pub is_forwards: bool,
     pub turn_targets: Option<(Vec3A, Vec3A)>,
 }
 
@@ -118,9 +187,12 @@ impl GroundBasedShot {
                 param: [0., 0., 0.],
                 type_: PathType::LSL,
             },
+            powerslide: None,
+            max_turn_speed: None,
             path_endpoint: PosRot { pos: Vec3A::ZERO, rot: 0. },
             shot_type: ShotType::Ground,
             jump_time: None,
+            is_forwards: true,
             turn_targets: None,
         }
     }
@@ -137,9 +209,16 @@ impl GroundBasedShot {
                 target.path.length(),
             ];
 
+            // the powerslide gets driven first, so it's part of the first subpath
+            let mut first_samples = match &target.powerslide {
+                Some(powerslide) => get_samples_from_path(powerslide, 0., powerslide.length(), Self::STEP_DISTANCE),
+                None => Vec::new(),
+            };
+            first_samples.append(&mut get_samples_from_path(&target.path, 0., segment_distances[0], Self::STEP_DISTANCE));
+
             // the samples for each subpath
             let raw_samples = [
-                get_samples_from_path(&target.path, 0., segment_distances[0], Self::STEP_DISTANCE),
+                first_samples,
                 get_samples_from_path(&target.path, segment_distances[0], segment_distances[1], Self::STEP_DISTANCE),
                 get_samples_from_path(&target.path, segment_distances[1], segment_distances[2], Self::STEP_DISTANCE),
                 get_samples_from_line(path_endpoint, direction, target.distances[3], Self::STEP_DISTANCE),
@@ -171,13 +250,32 @@ impl GroundBasedShot {
             all_samples,
             samples,
             path: target.path,
+            powerslide: target.powerslide,
+            max_turn_speed: target.max_turn_speed,
             path_endpoint,
             shot_type: target.shot_type,
             jump_time: target.jump_time,
+            is_forwards: target.is_forwards,
             turn_targets: target.turn_targets,
         }
     }
 
+    /// Sample the path (including the powerslide, if there is one) at the given distance
+    pub fn sample(&self, distance: f32) -> PosRot {
+        match &self.powerslide {
+            Some(powerslide) => {
+                let powerslide_length = powerslide.length();
+
+                if distance < powerslide_length {
+                    powerslide.sample(distance)
+                } else {
+                    self.path.sample(distance - powerslide_length)
+                }
+            }
+            None => self.path.sample(distance),
+        }
+    }
+
     fn find_min_distance_in_segment_index(&self, segment: usize, target: Vec3A) -> (usize, f32) {
         let mut min_distance = f32::MAX;
         let mut start_index = 0;
@@ -243,9 +341,12 @@ impl GroundBasedShot {
 }
 
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Options {
     pub all: bool,
     pub use_absolute_max_values: bool,
+    pub simulate_aerials: bool,
+    pub debug: bool,
     pub min_slice: usize,
     pub max_slice: usize,
 }
@@ -259,10 +360,14 @@ impl Options {
                 let max_slice = options.max_slice.unwrap_or(max_slices);
                 let use_absolute_max_values = options.use_absolute_max_values.unwrap_or(false);
                 let all = options.all.unwrap_or(false);
+                let simulate_aerials = options.simulate_aerials.unwrap_or(false);
+                let debug = options.debug.unwrap_or(false);
 
                 Self {
                     all,
                     use_absolute_max_values,
+                    simulate_aerials,
+                    debug,
                     min_slice,
                     max_slice,
                 }
@@ -272,25 +377,82 @@ impl Options {
                 max_slice: max_slices,
                 use_absolute_max_values: false,
                 all: false,
+                simulate_aerials: false,
+                debug: false,
             },
         }
     }
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct TargetLocation {
-    pub left: Vec3A,
-    pub right: Vec3A,
+impl From<Options> for TargetOptions {
+    #[inline]
+    fn from(options: Options) -> Self {
+        Self {
+            min_slice: Some(options.min_slice),
+            max_slice: Some(options.max_slice),
+            use_absolute_max_values: Some(options.use_absolute_max_values),
+            all: Some(options.all),
+            simulate_aerials: Some(options.simulate_aerials),
+            debug: Some(options.debug),
+        }
+    }
+}
+
+/// Where the ball should be shot
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetLocation {
+    /// Between two posts, and optionally between a bottom and top height
+    Posts { left: Vec3A, right: Vec3A, window: Option<(f32, f32)> },
+    /// Towards a point, up to the tolerance angle (in radians) off to either side
+    Point { point: Vec3A, tolerance_angle: f32 },
+    /// Into a polygon, looking from above
+    Area(Vec<Vec3A>),
+    /// Anywhere but between two posts
+    AwayFromPosts { left: Vec3A, right: Vec3A },
+    /// Anywhere but within the tolerance angle (in radians) of a point
+    AwayFromPoint { point: Vec3A, tolerance_angle: f32 },
 }
 
 impl TargetLocation {
     #[inline]
     pub const fn new(left: Vec3A, right: Vec3A) -> Self {
-        Self { left, right }
+        Self::Posts { left, right, window: None }
+    }
+
+    #[inline]
+    pub const fn window(left: Vec3A, right: Vec3A, bottom: f32, top: f32) -> Self {
+        Self::Posts {
+            left,
+            right,
+            window: Some((bottom, top)),
+        }
+    }
+
+    /// Get the posts that the shot vector is picked between
+    ///
+    /// Posts (and windows) are corrected for the radius of the ball, points and areas aren't
+    /// Posts to avoid are moved out by the radius of the ball instead
+    pub fn post_correction(&self, ball_location: Vec3A, ball_radius: f32) -> PostCorrection {
+        match self {
+            Self::Posts { left, right, window } => {
+                let post_info = PostCorrection::from(ball_location, ball_radius, *left, *right);
+
+                match *window {
+                    Some((bottom, top)) => post_info.with_window(ball_radius, bottom, top),
+                    None => post_info,
+                }
+            }
+            Self::Point { point, tolerance_angle } => PostCorrection::from_point(ball_location, *point, *tolerance_angle),
+            Self::Area(polygon) => PostCorrection::from_area(ball_location, polygon),
+            Self::AwayFromPosts { left, right } => PostCorrection::avoid(ball_location, ball_radius, *left, *right),
+            Self::AwayFromPoint { point, tolerance_angle } => PostCorrection::from_point(ball_location, *point, *tolerance_angle).avoiding(),
+        }
     }
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Target {
     pub car_index: usize,
     pub location: Option<TargetLocation>,
@@ -301,10 +463,10 @@ pub struct Target {
 
 impl Target {
     #[inline]
-    pub const fn new(target_left: Vec3A, target_right: Vec3A, car_index: usize, options: Options) -> Self {
+    pub const fn new(location: TargetLocation, car_index: usize, options: Options) -> Self {
         Self {
             car_index,
-            location: Some(TargetLocation::new(target_left, target_right)),
+            location: Some(location),
             options,
             shot: None,
             confirmed: false,
@@ -1,3 +1,4 @@
+use crate::constants::MAX_TOLERANCE_ANGLE;
 use dubins_paths::{DubinsPath, PosRot};
 use glam::Vec3A;
 use std::ops::{Add, Mul, Sub};
@@ -89,15 +90,38 @@ pub const fn flatten(vec: Vec3A) -> Vec3A {
 //     Vec3A::new(vec.y, -vec.x, 0.)
 // }
 
-// fn rotate_2d(vec: Vec3A, angle: f32) -> Vec3A {
-//     Vec3A::new(angle.cos() * vec.x - angle.sin() * vec.y, angle.sin() * vec.x + angle.cos() * vec.y, vec.z)
-// }
+#[inline]
+fn rotate_2d(vec: Vec3A, angle: f32) -> Vec3A {
+    Vec3A::new(angle.cos() * vec.x - angle.sin() * vec.y, angle.sin() * vec.x + angle.cos() * vec.y, vec.z)
+}
+
+/// If the point is inside of the polygon, looking from above
+fn is_point_in_polygon(point: Vec3A, polygon: &[Vec3A]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+
+        if (a.y > point.y) != (b.y > point.y) && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+
+        j = i;
+    }
+
+    inside
+}
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct PostCorrection {
     pub target_left: Vec3A,
     pub target_right: Vec3A,
     pub fits: bool,
+    /// The lowest and highest that the center of the ball can be aimed
+    pub window: Option<(f32, f32)>,
+    /// If the posts are the edges of a cone that the ball shouldn't go into, instead of one that it should
+    pub avoid: bool,
 }
 
 impl PostCorrection {
@@ -131,31 +155,149 @@ impl PostCorrection {
             target_left: left_corrected,
             target_right: right_corrected,
             fits: new_goal_width * new_goal_perp.dot(ball_to_goal).abs() > ball_radius * 2.,
+            window: None,
+            avoid: false,
         }
     }
 
+    /// Shoot the ball towards the point, up to the tolerance angle off to either side
+    ///
+    /// The tolerance angle is clamped to just under a quarter turn
+    pub fn from_point(ball_location: Vec3A, point: Vec3A, tolerance_angle: f32) -> Self {
+        let ball_to_point = flatten(point - ball_location);
+        let tolerance_angle = tolerance_angle.clamp(0., MAX_TOLERANCE_ANGLE);
+
+        // left is clockwise, looking from above
+        let left_post = flatten(ball_location) + rotate_2d(ball_to_point, -tolerance_angle);
+        let right_post = flatten(ball_location) + rotate_2d(ball_to_point, tolerance_angle);
+
+        Self {
+            target_left: Vec3A::new(left_post.x, left_post.y, point.z),
+            target_right: Vec3A::new(right_post.x, right_post.y, point.z),
+            fits: true,
+            window: None,
+            avoid: false,
+        }
+    }
+
+    /// Shoot the ball into the polygon, between the corners that are the furthest to the left and right
+    ///
+    /// If the ball is already in the polygon, it gets shot towards the middle of it
+    pub fn from_area(ball_location: Vec3A, polygon: &[Vec3A]) -> Self {
+        let center = polygon.iter().copied().sum::<Vec3A>() / polygon.len() as f32;
+
+        if is_point_in_polygon(ball_location, polygon) {
+            return Self {
+                target_left: center,
+                target_right: center,
+                fits: true,
+                window: None,
+                avoid: false,
+            };
+        }
+
+        let forward = flatten(center - ball_location).normalize_or_zero();
+        let left = forward.cross(Vec3A::Z);
+        let angle = |corner: &&Vec3A| {
+            let ball_to_corner = flatten(**corner - ball_location);
+            left.dot(ball_to_corner).atan2(forward.dot(ball_to_corner))
+        };
+
+        Self {
+            target_left: *polygon.iter().max_by(|a, b| angle(a).total_cmp(&angle(b))).unwrap_or(&center),
+            target_right: *polygon.iter().min_by(|a, b| angle(a).total_cmp(&angle(b))).unwrap_or(&center),
+            fits: true,
+            window: None,
+            avoid: false,
+        }
+    }
+
+    /// Keep the ball out from between the posts, like our own goal
+    ///
+    /// The posts are moved out by the ball's radius so the ball can't clip the edge of the goal
+    pub fn avoid(ball_location: Vec3A, ball_radius: f32, target_left: Vec3A, target_right: Vec3A) -> Self {
+        Self {
+            target_left: target_left + (target_left - ball_location).normalize_or_zero().cross(Vec3A::Z) * ball_radius,
+            target_right: target_right + (target_right - ball_location).normalize_or_zero().cross(-Vec3A::Z) * ball_radius,
+            fits: true,
+            window: None,
+            avoid: true,
+        }
+    }
+
+    /// Keep the ball out of the cone instead of in it
+    #[inline]
+    pub fn avoiding(self) -> Self {
+        Self { avoid: true, ..self }
+    }
+
+    /// Only aim the ball between the bottom and top heights, like under a crossbar
+    ///
+    /// The window is aimed at in a straight line, so the ball's drop from gravity isn't accounted for
+    pub fn with_window(self, ball_radius: f32, bottom: f32, top: f32) -> Self {
+        let window = (bottom + ball_radius, top - ball_radius);
+
+        Self {
+            fits: self.fits && window.0 < window.1,
+            window: Some(window),
+            ..self
+        }
+    }
+
+    /// The directions from the ball to the left and right corrected posts, which are the edges of the cone that the ball can be shot in
+    #[inline]
+    pub fn get_shot_cone(&self, ball_location: Vec3A) -> (Vec3A, Vec3A) {
+        (
+            (self.target_left - ball_location).normalize_or_zero(),
+            (self.target_right - ball_location).normalize_or_zero(),
+        )
+    }
+
     pub fn get_shot_vector_target(&self, car_location: Vec3A, ball_location: Vec3A) -> Vec3A {
-        let left_vector = (self.target_left - ball_location).normalize_or_zero();
+        let (left_vector, right_vector) = self.get_shot_cone(ball_location);
         let left_vector_flat = flatten(left_vector);
-        let right_vector = (self.target_right - ball_location).normalize_or_zero();
         let right_vector_flat = flatten(right_vector);
         let car_to_ball_flat = flatten(ball_location - car_location).normalize_or_zero();
 
+        if self.avoid {
+            // anywhere outside of the cone is fine, so only turn the shot as much as is needed to get out of it
+            return match clamp_index(car_to_ball_flat, left_vector_flat, right_vector_flat) {
+                ClampDirection::Middle if left_vector_flat.dot(car_to_ball_flat) > right_vector_flat.dot(car_to_ball_flat) => left_vector_flat.normalize_or_zero(),
+                ClampDirection::Middle => right_vector_flat.normalize_or_zero(),
+                ClampDirection::Left | ClampDirection::Right => car_to_ball_flat,
+            };
+        }
+
         // All of this is so that the returned vector will always point towards the target z
-        match clamp_index(car_to_ball_flat, left_vector_flat, right_vector_flat) {
+        let target = match clamp_index(car_to_ball_flat, left_vector_flat, right_vector_flat) {
             ClampDirection::Middle => {
                 // angle_between between uses acos, will always be between 0 and pi
                 let car_to_left_angle = left_vector_flat.angle_between(car_to_ball_flat);
-                let left_to_right_angle = left_vector_flat.angle_between(right_vector);
+                let left_to_right_angle = left_vector_flat.angle_between(right_vector_flat);
 
-                // convert angles to a value between 0 and 1
-                let t = car_to_left_angle / left_to_right_angle;
+                if left_to_right_angle < f32::EPSILON {
+                    // the cone has no width, like a point target with no tolerance or an area that the ball is already in
+                    self.target_left
+                } else {
+                    // convert angles to a value between 0 and 1
+                    let t = car_to_left_angle / left_to_right_angle;
 
-                (lerp(self.target_left, self.target_right, t) - ball_location).normalize_or_zero()
+                    lerp(self.target_left, self.target_right, t)
+                }
             }
-            ClampDirection::Left => left_vector,
-            ClampDirection::Right => right_vector,
-        }
+            ClampDirection::Left => self.target_left,
+            ClampDirection::Right => self.target_right,
+        };
+
+        // keep the shot as flat as the window allows
+        // this is a straight-line approximation, since how much the ball drops on the way depends on how hard it's hit, which isn't known here
+        // the ball will cross the goal line lower than this aims for, which is only a problem for slow shots from far away under a low top
+        let target = match self.window {
+            Some((bottom, top)) => Vec3A::new(target.x, target.y, ball_location.z.max(bottom).min(top)),
+            None => target,
+        };
+
+        (target - ball_location).normalize_or_zero()
     }
 }
 
@@ -185,3 +327,137 @@ pub fn vertex_quadratic_solve_for_x(a: f32, h: f32, k: f32, y: f32) -> (f32, f32
     let v_sqrt = ((y - k) / a).sqrt();
     (h + v_sqrt, h - v_sqrt)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten, PostCorrection};
+    use glam::Vec3A;
+    use std::f32::consts::PI;
+
+    const BALL_RADIUS: f32 = 92.75;
+
+    #[test]
+    pub fn post_correction_shot_cone() {
+        let ball_location = Vec3A::new(0., 4000., BALL_RADIUS);
+        let post_info = PostCorrection::from(ball_location, BALL_RADIUS, Vec3A::new(800., 5120., 0.), Vec3A::new(-800., 5120., 0.));
+
+        assert!(post_info.fits);
+
+        let (left_vector, right_vector) = post_info.get_shot_cone(ball_location);
+        assert!((left_vector.x + right_vector.x).abs() < 0.001);
+        assert!(left_vector.y > 0. && right_vector.y > 0.);
+    }
+
+    #[test]
+    pub fn post_correction_sharp_angle() {
+        // from outside of the left post, the left post blocks part of the goal
+        let ball_location = Vec3A::new(2000., 4500., BALL_RADIUS);
+        let post_info = PostCorrection::from(ball_location, BALL_RADIUS, Vec3A::new(800., 5120., 0.), Vec3A::new(-800., 5120., 0.));
+
+        assert!(post_info.target_left.x < 800.);
+        assert_eq!(post_info.target_right, Vec3A::new(-800., 5120., 0.));
+    }
+
+    #[test]
+    pub fn window_aims_under_crossbar() {
+        let ball_location = Vec3A::new(0., 4000., 1200.);
+        let left_post = Vec3A::new(800., 5120., 0.);
+        let right_post = Vec3A::new(-800., 5120., 0.);
+        let post_info = PostCorrection::from(ball_location, BALL_RADIUS, left_post, right_post).with_window(BALL_RADIUS, 0., 642.775);
+
+        assert!(post_info.fits);
+
+        // the ball is above the crossbar, so it has to be hit down to go in
+        // the shot vector is aimed in a straight line, so this is where the ball would cross the goal line without gravity
+        let shot_vector = post_info.get_shot_vector_target(Vec3A::new(0., 3000., 17.), ball_location);
+        let goal_line_height = ball_location.z + shot_vector.z / shot_vector.y * (5120. - ball_location.y);
+        assert!((goal_line_height - (642.775 - BALL_RADIUS)).abs() < 1.);
+
+        let too_small = PostCorrection::from(ball_location, BALL_RADIUS, left_post, right_post).with_window(BALL_RADIUS, 0., BALL_RADIUS * 1.5);
+        assert!(!too_small.fits);
+    }
+
+    #[test]
+    pub fn point_target_cone() {
+        let ball_location = Vec3A::new(0., 0., BALL_RADIUS);
+        let post_info = PostCorrection::from_point(ball_location, Vec3A::new(0., 2000., 0.), PI / 4.);
+
+        // looking down the y axis, left is +x
+        assert!((post_info.target_left - Vec3A::new(2000. * (PI / 4.).sin(), 2000. * (PI / 4.).cos(), 0.)).length() < 0.1);
+        assert!((post_info.target_right.x + post_info.target_left.x).abs() < 0.1);
+
+        // coming in from way off to the side, the shot gets clamped to the edge of the cone
+        let shot_vector = post_info.get_shot_vector_target(Vec3A::new(-3000., -100., 17.), ball_location);
+        assert!((flatten(shot_vector).normalize().angle_between(Vec3A::Y) - PI / 4.).abs() < 0.01);
+    }
+
+    #[test]
+    pub fn area_target_corners() {
+        let corner = [
+            Vec3A::new(3000., 4000., 0.),
+            Vec3A::new(4000., 4000., 0.),
+            Vec3A::new(4000., 5000., 0.),
+            Vec3A::new(3000., 5000., 0.),
+        ];
+
+        let post_info = PostCorrection::from_area(Vec3A::new(3500., 0., BALL_RADIUS), &corner);
+        assert_eq!(post_info.target_left, corner[1]);
+        assert_eq!(post_info.target_right, corner[0]);
+
+        let post_info = PostCorrection::from_area(Vec3A::new(3500., 4500., BALL_RADIUS), &corner);
+        assert_eq!(post_info.target_left, Vec3A::new(3500., 4500., 0.));
+    }
+
+    #[test]
+    pub fn zero_width_cones() {
+        let car_location = Vec3A::new(500., -1000., 17.);
+        let ball_location = Vec3A::new(0., 0., BALL_RADIUS);
+
+        let point = Vec3A::new(0., 2000., 0.);
+        let shot_vector = PostCorrection::from_point(ball_location, point, 0.).get_shot_vector_target(car_location, ball_location);
+        assert!((shot_vector - (point - ball_location).normalize()).length() < 0.001);
+
+        let corner = [
+            Vec3A::new(-500., -500., 0.),
+            Vec3A::new(500., -500., 0.),
+            Vec3A::new(500., 500., 0.),
+            Vec3A::new(-500., 500., 0.),
+        ];
+        let ball_location = Vec3A::new(200., 100., BALL_RADIUS);
+        let shot_vector = PostCorrection::from_area(ball_location, &corner).get_shot_vector_target(car_location, ball_location);
+        assert!((shot_vector - (Vec3A::ZERO - ball_location).normalize()).length() < 0.001);
+    }
+
+    #[test]
+    pub fn point_target_tolerance_is_clamped() {
+        let ball_location = Vec3A::new(0., 0., BALL_RADIUS);
+        let post_info = PostCorrection::from_point(ball_location, Vec3A::new(0., 2000., 0.), PI);
+
+        // both edges of the cone are still in front of the ball, on their own sides
+        assert!(post_info.target_left.y > 0. && post_info.target_right.y > 0.);
+        assert!(post_info.target_left.x > 0. && post_info.target_right.x < 0.);
+
+        // a car behind the ball hits it straight at the point, and one off to the side hits it at the edge of the cone
+        let shot_vector = post_info.get_shot_vector_target(Vec3A::new(0., -1000., 17.), ball_location);
+        assert!(flatten(shot_vector).normalize().y > 0.99);
+
+        let shot_vector = post_info.get_shot_vector_target(Vec3A::new(2000., -1., 17.), ball_location);
+        assert!(shot_vector.x < 0. && shot_vector.y > 0.);
+    }
+
+    #[test]
+    pub fn away_from_own_goal() {
+        let ball_location = Vec3A::new(0., -4000., BALL_RADIUS);
+        // our goal is behind the ball, so left and right are flipped from the ball's point of view
+        let post_info = PostCorrection::avoid(ball_location, BALL_RADIUS, Vec3A::new(-800., -5120., 0.), Vec3A::new(800., -5120., 0.));
+
+        // going up the field is already fine
+        let shot_vector = post_info.get_shot_vector_target(Vec3A::new(300., -4500., 17.), ball_location);
+        assert!((shot_vector - Vec3A::new(-300., 500., 0.).normalize()).length() < 0.001);
+
+        // going towards the goal, but a little bit towards +x, gets turned out past the post on that side
+        let shot_vector = post_info.get_shot_vector_target(Vec3A::new(-100., -3000., 17.), ball_location);
+        let past_goal_line = ball_location + shot_vector / shot_vector.y.abs() * 1120.;
+        assert!(past_goal_line.x > 800. + BALL_RADIUS * 0.9);
+    }
+}
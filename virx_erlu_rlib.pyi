@@ -43,6 +43,7 @@ def load_soccer_throwback() -> None:
 try:
     from rlbot.messages.flat.MutatorSettings import MutatorSettings
     from rlbot.utils.structures.game_data_struct import GameTickPacket
+    from rlbot.agents.base_agent import SimpleControllerState
 except ImportError:
     pass
 
@@ -68,13 +69,40 @@ class ShotType(Enum):
     Aerial: int = 3
 
 
+class ShotRejection(Enum):
+    ShotTypeDisabled: int = 0
+    TooSoonAfterLanding: int = 1
+    OutOfField: int = 2
+    PostCorrection: int = 3
+    PathTooLong: int = 4
+    NotEnoughJumpTime: int = 5
+    TooSlow: int = 6
+    AerialTooFar: int = 7
+    AerialUnreachable: int = 8
+    AerialSimulation: int = 9
+
+
+class KickoffSpawn(Enum):
+    Diagonal: int = 0
+    OffCenter: int = 1
+    Center: int = 2
+
+
+class KickoffOutcome(Enum):
+    Win: int = 0
+    FiftyFifty: int = 1
+    Lose: int = 2
+
+
 class TargetOptions:
     min_slice: Optional[int]
     max_slice: Optional[int]
     use_absolute_max_values: Optional[bool]
     all: Optional[bool]
+    simulate_aerials: Optional[bool]
+    debug: Optional[bool]
 
-    def __init__(self, min_slice: Optional[int]=None, max_slice: Optional[int]=None, use_absolute_max_values: Optional[bool]=None, all: Optional[bool]=None) -> TargetOptions:
+    def __init__(self, min_slice: Optional[int]=None, max_slice: Optional[int]=None, use_absolute_max_values: Optional[bool]=None, all: Optional[bool]=None, simulate_aerials: Optional[bool]=None, debug: Optional[bool]=None) -> TargetOptions:
         """
         This class doesn't actually have a custom constructor.
         Due to limitations in PyO3, __new__ must be used instead of __init__.
@@ -82,8 +110,11 @@ class TargetOptions:
 
         NOTE:
         You can still call TargetOptions() and pass in parameters to make a new instance.
+
+        simulate_aerials: Fly each aerial tick-by-tick with the car's angular dynamics before accepting it, which is slower but rejects aerials that turn out to be impossible
+        debug: Record why each ball slice was rejected in BasicShotInfo.rejections, which is always empty when this isn't True
         """
-    def __new__(self, min_slice: Optional[int]=None, max_slice: Optional[int]=None, use_absolute_max_values: Optional[bool]=None, all: Optional[bool]=None) -> TargetOptions: ...
+    def __new__(self, min_slice: Optional[int]=None, max_slice: Optional[int]=None, use_absolute_max_values: Optional[bool]=None, all: Optional[bool]=None, simulate_aerials: Optional[bool]=None, debug: Optional[bool]=None) -> TargetOptions: ...
     def __str__(self) -> str: ...
     def __repr__(self) -> str: ...
 
@@ -116,6 +147,29 @@ def get_num_ball_slices() -> int:
     """
 
 
+class PostCorrectionInfo:
+    target_left: tuple[float, float, float]
+    target_right: tuple[float, float, float]
+    fits: bool
+    left_vector: tuple[float, float, float]
+    right_vector: tuple[float, float, float]
+    cone_angle: float
+
+    def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+def get_post_correction(ball_slice: BallSlice, left_target: tuple[float, float, float], right_target: tuple[float, float, float]) -> PostCorrectionInfo:
+    """
+    Corrects the goal posts for the radius of the ball at the given slice
+
+    target_left, target_right: The posts after being moved in so that the ball won't clip them
+    fits: If the ball can fit between the corrected posts
+    left_vector, right_vector: The directions from the ball to each corrected post, the edges of the cone that the ball can be shot in
+    cone_angle: The angle, in radians, between the two directions on the ground
+    """
+
+
 def new_target(left_target: tuple[float, float, float], right_target: tuple[float, float, float], car_index: int, options: Optional[TargetOptions]=None) -> int:
     """
     Creates a new target and returns the target's I.D.
@@ -124,6 +178,57 @@ def new_target(left_target: tuple[float, float, float], right_target: tuple[floa
     """
 
 
+def new_window_target(left_target: tuple[float, float, float], right_target: tuple[float, float, float], bottom: float, top: float, car_index: int, options: Optional[TargetOptions]=None) -> int:
+    """
+    Creates a new target where the ball also has to go between the bottom and top heights, like under a crossbar, and returns the target's I.D.
+
+    Shots aim as flat as the window allows, so the ball is hit down if it's above the window and up if it's below it.
+    The window is aimed at in a straight line that ignores gravity, so slow shots from far away will cross the goal line lower than aimed.
+
+    Targets get automatically deleted upon calling tick() if it hasn't been confirmed.
+    """
+
+
+def new_point_target(point: tuple[float, float, float], tolerance_angle: float, car_index: int, options: Optional[TargetOptions]=None) -> int:
+    """
+    Creates a new target towards a point, like a teammate or a corner, and returns the target's I.D.
+
+    tolerance_angle: How far off to either side of the point the ball may be shot, in radians, up to just under pi / 2
+
+    Targets get automatically deleted upon calling tick() if it hasn't been confirmed.
+    """
+
+
+def new_area_target(polygon: list[tuple[float, float, float]], car_index: int, options: Optional[TargetOptions]=None) -> int:
+    """
+    Creates a new target into an area of the field, like the back wall for a pass, and returns the target's I.D.
+
+    polygon: The corners of the area, in order, looking from above - there must be at least 3
+
+    Targets get automatically deleted upon calling tick() if it hasn't been confirmed.
+    """
+
+
+def new_away_target(left_target: tuple[float, float, float], right_target: tuple[float, float, float], car_index: int, options: Optional[TargetOptions]=None) -> int:
+    """
+    Creates a new target to anywhere except between two posts, like your own goal, and returns the target's I.D.
+
+    The shot is turned just enough to get it outside of the posts, which are moved out by the ball's radius.
+
+    Targets get automatically deleted upon calling tick() if it hasn't been confirmed.
+    """
+
+
+def new_away_from_point_target(point: tuple[float, float, float], tolerance_angle: float, car_index: int, options: Optional[TargetOptions]=None) -> int:
+    """
+    Creates a new target to anywhere except towards a point, and returns the target's I.D.
+
+    tolerance_angle: How far off to either side of the point the ball may not be shot, in radians, up to just under pi / 2
+
+    Targets get automatically deleted upon calling tick() if it hasn't been confirmed.
+    """
+
+
 def new_any_target(car_index: int, options: Optional[TargetOptions]=None) -> int:
     """
     Creates a new target to anywhere and returns the target's I.D.
@@ -164,6 +269,10 @@ class BasicShotInfo:
     shot_type: Optional[ShotType]
     shot_vector: tuple[float, float, float]
     is_forwards: bool
+    rejections: list[tuple[float, ShotRejection]]
+    """
+    The time of each ball slice that was checked and why it couldn't be used, only filled in when the target has debug enabled
+    """
 
     def __str__(self) -> str: ...
     def __repr__(self) -> str: ...
@@ -198,3 +307,264 @@ def get_data_for_shot_with_target(target_id: int) -> AdvancedShotInfo:
     """
     Gets information about the found shot
     """
+
+
+class ShotControls:
+    throttle: float
+    steer: float
+    boost: bool
+    handbrake: bool
+    cross_track_error: float
+    jump: bool
+    pitch: float
+    yaw: float
+    roll: float
+    revalidate: bool
+
+    def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+def get_controls_for_shot(target_id: int) -> ShotControls:
+    """
+    Gets the controls that keep the car on schedule for the found shot
+
+    Ground shots:
+    Steering is a pure pursuit controller that follows the path of the shot
+    cross_track_error: How far the car is from the path, positive when the car is to the right of it
+
+    Aerial shots:
+    Jumps, points the car and boosts to get to the final target of the aerial at the time of the shot
+    revalidate: True if the aerial no longer looks possible and a new shot should be searched for
+    """
+
+
+class FollowUpInfo:
+    found: bool
+    time: float
+    final_target: tuple[float, float, float]
+    ball_location: tuple[float, float, float]
+
+    def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+def get_follow_up_for_shot(target_id: int, max_time: float=2., flip_reset: bool=False) -> FollowUpInfo:
+    """
+    Checks if the car can touch the ball again in the air after the aerial shot for the target
+
+    max_time: How many seconds after the first touch to search for a second touch
+    flip_reset: Set to True if the car will touch the ball with its wheels and get its jump back
+
+    Raises a ValueError if the shot is after the end of the ball prediction
+    """
+
+
+class RenderLine:
+    name: str
+    points: list[tuple[float, float, float]]
+    color: tuple[int, int, int, int]
+    """
+    (alpha, red, green, blue)
+    """
+
+    def __repr__(self) -> str: ...
+
+
+class RenderMarker:
+    name: str
+    location: tuple[float, float, float]
+    size: float
+    color: tuple[int, int, int, int]
+    """
+    (alpha, red, green, blue)
+    """
+
+    def __repr__(self) -> str: ...
+
+
+class ShotRender:
+    lines: list[RenderLine]
+    markers: list[RenderMarker]
+
+    def __repr__(self) -> str: ...
+
+
+def get_render_for_shot(target_id: int) -> ShotRender:
+    """
+    Gets the lines and markers to draw for the shot of the target
+
+    Lines: segment_0, segment_1 and segment_2 (the Dubins path, ground shots only), final_straight, shot_vector, and corrected_goal (targets with a location only) or avoided_cone (away targets only)
+    Markers: contact_point, landing_point, and left_post and right_post (targets with a location only) or left_edge and right_edge (away targets only)
+
+    Example:
+    for line in render.lines:
+        renderer.draw_polyline_3d(line.points, renderer.create_color(*line.color))
+    for marker in render.markers:
+        renderer.draw_rect_3d(marker.location, marker.size, marker.size, True, renderer.create_color(*marker.color), centered=True)
+    """
+
+
+class ShadowInfo:
+    location: tuple[float, float, float]
+    facing: tuple[float, float, float]
+    ball_time: Optional[float]
+    time: Optional[float]
+
+    def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+def get_shadow_position(car_index: int, opponent_index: int, goal: tuple[float, float, float], distance: float) -> ShadowInfo:
+    """
+    Gets where the car should be to stay between the ball and the goal when the opponent gets to the ball
+
+    distance: How far from the ball, towards the goal, the car should be
+
+    location: Where the car should drive to
+    facing: The direction the car should be facing when it gets there, which is towards the ball
+    ball_time: The game time when the opponent can first get to the ball, turning to hit it towards the goal, or None if they can't before the end of the ball prediction, in which case location is for the last slice
+    time: How many seconds it will take the car to get there, or None if it can't before the end of the ball prediction
+
+    Raises a ValueError if car_index and opponent_index are the same
+    """
+
+
+class KickoffInfo:
+    spawn: KickoffSpawn
+    target: tuple[float, float, float]
+    boost_time: float
+    dodge_time: Optional[float]
+    arrival_time: Optional[float]
+    opponent_arrival_time: Optional[float]
+    outcome: KickoffOutcome
+
+    def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+def get_kickoff_plan(car_index: int, opponent_index: Optional[int]=None) -> KickoffInfo:
+    """
+    Plans the car's kickoff from its spawn location, before the kickoff starts
+
+    opponent_index: The opponent to predict the kickoff against, who is assumed to boost straight at the ball and dodge when close to it; if None, an opponent on the same spawn on the other side of the field is assumed
+
+    spawn: Which standard kickoff spawn the car is on
+    target: Where the car should drive to, which is slightly to the car's side of the ball on diagonal and off-center kickoffs
+    boost_time: How many seconds the car should boost for
+    dodge_time: How many seconds from now the car should jump to dodge into the ball, or None if it shouldn't dodge
+    arrival_time: How many seconds it will take the car to touch the ball
+    opponent_arrival_time: How many seconds it will take the opponent to touch the ball
+    outcome: Whether the car should win the kickoff, lose it, or if it will be a 50/50
+
+    Raises an AssertionError if the car isn't waiting on a kickoff spawn, if the ball isn't on the center spot, or if the loaded arena isn't the standard soccar arena
+    """
+
+
+def is_dribbling(car_index: int) -> bool:
+    """
+    Checks if the ball is resting on the roof of the car at the given index
+    """
+
+
+class DribbleControls:
+    ball_offset: tuple[float, float, float]
+    throttle: float
+    steer: float
+    boost: bool
+    flick: bool
+    fall_time: Optional[float]
+
+    def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+def get_dribble_controls(target_id: int, defender_index: Optional[int]=None) -> DribbleControls:
+    """
+    Gets the controls to carry the ball on the roof of the target's car towards the target, or anywhere ahead for targets from new_any_target
+
+    ball_offset: Where the ball is relative to the center of the roof, in the car's local coordinates
+    flick: True when a flick now would send the ball between the target's posts, or when the defender is driving in to challenge from in front
+    fall_time: How long until the ball rolls off of the roof with these controls, or None if it stays on for the next 2 seconds
+
+    Raises an AssertionError if the ball isn't on the car's roof
+    """
+
+
+class CarSim:
+    time: float
+    location: tuple[float, float, float]
+    velocity: tuple[float, float, float]
+    angular_velocity: tuple[float, float, float]
+    forward: tuple[float, float, float]
+    right: tuple[float, float, float]
+    up: tuple[float, float, float]
+    boost: float
+    """
+    How much boost the car can use, which is inf with unlimited boost and 0 with no boost
+    """
+    on_ground: bool
+
+    def step(self, controls: SimpleControllerState, ticks: int=1):
+        """
+        Simulates the car at 120tps for the given number of ticks while holding the same controls
+
+        Driving is only simulated on the floor, and dodges aren't simulated - a second jump is always a double jump
+        """
+    def __repr__(self) -> str: ...
+
+
+def new_car_sim(car_index: int) -> CarSim:
+    """
+    Creates a car simulation that starts from the current state of the car at the given index
+    """
+
+
+def start_recording() -> None:
+    """
+    Starts recording the loaded arena, mutators, every call to tick, every target change, and every shot search with its result
+
+    The recording starts with the last call to tick and the targets that already exist, so their indices stay the same when it's replayed
+
+    Any recording that was already in progress is thrown away
+    """
+
+
+def stop_recording(path: str) -> None:
+    """
+    Stops recording and saves the recording to the given file, so it can be attached to bug reports
+    """
+
+
+class ReplayedShot:
+    game_time: float
+    target_index: int
+    recorded_time: Optional[float]
+    replayed_time: Optional[float]
+    matches: bool
+
+    def __repr__(self) -> str: ...
+
+
+def replay_recording(path: str) -> list[ReplayedShot]:
+    """
+    Feeds a recording back through the library in the same order, and returns every shot search with the time of the shot that was found when it was recorded and when it was replayed
+
+    The recording is replayed from a clean state, and the current game state, targets and mutators are put back afterwards
+    """
+
+
+def get_state_json() -> str:
+    """
+    Exports the game time, gravity, mutators, ball prediction, cars and targets (including their shots) as JSON
+
+    Only available when the library was built with the "serde" feature
+    """
+
+
+def get_state_bincode() -> bytes:
+    """
+    Exports the same state as get_state_json, but in bincode
+
+    Only available when the library was built with the "serde" feature
+    """